@@ -0,0 +1,186 @@
+//! Compares allocations and time spent deserializing a large search
+//! response into owned and borrowed venue representations
+//!
+//! `owned` parses into `foursquare::venue::Venue`, which also allocates the
+//! contact, stats and other fields `venue::borrowed::Venue` skips, so part
+//! of its difference from `borrowed` comes from those skipped fields.
+//! `mirrored` parses into owned types with exactly the fields of the
+//! borrowed types, isolating the saving of borrowing strings.
+//!
+//! Run with `cargo bench --bench deserialize`
+
+extern crate foursquare;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use foursquare::{Body, Response};
+use foursquare::venue::{borrowed, SearchResponse};
+
+/// Counts allocations made through the system allocator
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Owned counterparts of the `venue::borrowed` types
+mod mirrored {
+    #[derive(Deserialize)]
+    pub struct Venue {
+        pub id: String,
+        pub name: String,
+        pub location: Location,
+        #[serde(default)]
+        pub categories: Vec<Category>,
+        pub url: Option<String>,
+        pub rating: Option<f32>,
+        pub verified: Option<bool>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Location {
+        pub address: Option<String>,
+        #[serde(rename = "crossStreet")]
+        pub cross_street: Option<String>,
+        pub lat: f64,
+        pub lng: f64,
+        pub distance: Option<u32>,
+        #[serde(rename = "postalCode")]
+        pub postal_code: Option<String>,
+        pub cc: Option<String>,
+        pub city: Option<String>,
+        pub state: Option<String>,
+        pub country: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Icon {
+        pub prefix: String,
+        pub suffix: String,
+    }
+
+    #[derive(Deserialize)]
+    pub struct Category {
+        pub id: String,
+        pub name: String,
+        #[serde(rename = "pluralName")]
+        pub plural_name: String,
+        #[serde(rename = "shortName")]
+        pub short_name: String,
+        pub icon: Icon,
+        pub primary: Option<bool>,
+        #[serde(default)]
+        pub categories: Vec<Category>,
+    }
+
+    #[derive(Deserialize)]
+    pub struct SearchResponse {
+        pub venues: Vec<Venue>,
+    }
+}
+
+const VENUES: usize = 500;
+const ITERATIONS: usize = 200;
+
+fn payload() -> Vec<u8> {
+    let venues = (0..VENUES)
+        .map(|i| {
+            format!(
+                r#"{{"id": "4a{i:022}", "name": "Venue {i}",
+                    "contact": {{"phone": "7185551234"}},
+                    "location": {{"address": "{i} Meserole Ave",
+                                  "crossStreet": "at Manhattan Ave",
+                                  "lat": 40.72, "lng": -73.95,
+                                  "distance": {i}, "postalCode": "11222",
+                                  "cc": "US", "city": "Brooklyn",
+                                  "state": "NY", "country": "United States",
+                                  "formattedAddress": ["{i} Meserole Ave",
+                                                       "Brooklyn, NY 11222"]}},
+                    "categories": [{{"id": "4bf58dd8d48988d1e0931735",
+                                     "name": "Coffee Shop",
+                                     "pluralName": "Coffee Shops",
+                                     "shortName": "Coffee Shop",
+                                     "icon": {{"prefix": "https://ss3.4sqi.net/img/categories_v2/food/coffeeshop_",
+                                               "suffix": ".png"}},
+                                     "primary": true}}],
+                    "verified": false,
+                    "stats": {{"checkinsCount": 1, "usersCount": 1,
+                               "tipCount": 0}},
+                    "url": "http://example.com/{i}"}}"#,
+                i = i
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+    format!(
+        r#"{{"meta": {{"code": 200, "requestId": "1"}},
+            "response": {{"venues": [{}]}}}}"#,
+        venues
+    ).into_bytes()
+}
+
+fn measure<F>(name: &str, mut parse: F)
+where
+    F: FnMut() -> usize,
+{
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    let mut venues = 0;
+    for _ in 0..ITERATIONS {
+        venues += parse();
+    }
+    let elapsed = start.elapsed();
+    let allocated = ALLOCATIONS.load(Ordering::Relaxed) - allocations;
+    let nanos = elapsed.as_secs() * 1_000_000_000 + elapsed.subsec_nanos() as u64;
+    println!(
+        "{:<10} {:>8} allocations/parse {:>10} ns/parse",
+        name,
+        allocated / ITERATIONS,
+        nanos / ITERATIONS as u64
+    );
+    assert_eq!(venues, VENUES * ITERATIONS)
+}
+
+fn main() {
+    let body = Body::from(payload());
+    println!("search response with {} venues", VENUES);
+    measure("owned", || {
+        serde_json::from_slice::<Response<SearchResponse>>(body.as_bytes())
+            .unwrap()
+            .response
+            .venues
+            .len()
+    });
+    measure("mirrored", || {
+        serde_json::from_slice::<Response<mirrored::SearchResponse>>(
+            body.as_bytes(),
+        ).unwrap()
+            .response
+            .venues
+            .len()
+    });
+    measure("borrowed", || {
+        body.parse::<borrowed::SearchResponse>()
+            .unwrap()
+            .response
+            .venues
+            .len()
+    });
+}
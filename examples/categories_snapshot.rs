@@ -1,10 +1,13 @@
 extern crate env_logger;
 extern crate foursquare;
+extern crate serde_json;
 extern crate tokio_core;
 #[macro_use(quick_main)]
 extern crate error_chain;
 
 use std::env;
+use std::fs::File;
+use std::io::Write;
 
 use tokio_core::reactor::Core;
 
@@ -12,6 +15,11 @@ use foursquare::{Client, Credentials, Result};
 
 quick_main!(run);
 
+const VERSION: &str = "20171001";
+
+/// regenerates data/categories.json, the `category-snapshot` feature's
+/// embedded taxonomy, and data/categories.version, the api version it was
+/// fetched with
 fn run() -> Result<()> {
     drop(env_logger::init());
     match env::var("FS_CLIENT_ID").ok().and_then(|id| {
@@ -20,14 +28,21 @@ fn run() -> Result<()> {
         Some((id, sec)) => {
             let mut core = Core::new()?;
             let foursq = Client::new(
-                "20170801",
+                VERSION,
                 Credentials::client(id, sec),
                 &core.handle(),
             );
-            match core.run(foursq.venues().categories(&Default::default())) {
-                Ok(res) => println!("{:#?}", res),
-                Err(err) => println!("err {}", err),
-            }
+            let res = core.run(
+                foursq.venues().categories(&Default::default()),
+            )?;
+            serde_json::to_writer_pretty(
+                File::create("data/categories.json")?,
+                &res.response,
+            )?;
+            // no trailing newline, the file is embedded as SNAPSHOT_VERSION
+            File::create("data/categories.version")?.write_all(
+                VERSION.as_bytes(),
+            )?;
             Ok(())
         }
         _ => Err(
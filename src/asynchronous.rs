@@ -0,0 +1,256 @@
+//! An interface for interacting with Foursquare API from `std::future`
+//! based executors
+//!
+//! Operations return `ResponseFuture`s, which implement
+//! `std::future::Future`, are `Send`, and so may be `.await`ed from any
+//! executor, single or multi-threaded. Requests are driven by a reactor on a
+//! background thread owned by the client, so no reactor handle needs to be
+//! provided. Requests are sent when an operation is called rather than when
+//! its future is first polled.
+//!
+//! Requires the `std-future` feature, along with the `tls` or `rustls`
+//! feature, and Rust 1.36 or newer.
+//!
+//! This is an adapter over the futures 0.1 based `foursquare::Client`
+//! rather than a native `std::future` implementation. Each client starts
+//! its own thread running a tokio-core reactor, shared by the client's
+//! clones, so create one client and clone it rather than creating one per
+//! task. Every request is handed to that thread and its result handed back
+//! through a mutex guarded slot which wakes the awaiting task, costing two
+//! thread hops per request. A native implementation, driven by the awaiting
+//! executor itself, awaits a hyper release built on `std::future`.
+//!
+//! ```ignore
+//! use foursquare::Credentials;
+//! use foursquare::asynchronous::Client;
+//! use foursquare::venue::SearchOptions;
+//!
+//! async fn coffee(fs: &Client) -> foursquare::Result<()> {
+//!     let response = fs.venues()
+//!         .search(&SearchOptions::builder().near("Brooklyn, NY").build()?)
+//!         .await?;
+//!     for venue in response.response.venues {
+//!         println!("{}", venue.name);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use std::future::Future as StdFuture;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use tokio_core::reactor::Handle;
+
+use {Credentials, Future, Response, Result};
+use runtime::Runtime;
+use venue::{self, CategoriesOptions, CategoriesResponse, ExploreOptions,
+            ExploreResponse, HoursOptions, RecommendationsOptions,
+            RecommendationsResponse, SearchOptions, SearchResponse,
+            SuggestOptions, SuggestResponse, TipsOptions, TipsResponse,
+            VenueDetailsOptions, VenueHoursResponse, VenueResponse};
+
+/// Entry point interface for interacting with Foursquare API from
+/// `std::future` based executors
+#[derive(Clone)]
+pub struct Client {
+    runtime: Runtime,
+}
+
+impl Client {
+    /// Return a new client, starting its background thread
+    ///
+    /// version should be in `YYYYMMDD` format
+    pub fn new<V>(version: V, credentials: Credentials) -> Result<Self>
+    where
+        V: Into<String>,
+    {
+        let version = version.into();
+        Self::with_client(
+            move |handle| ::Client::new(version, credentials, handle),
+        )
+    }
+
+    /// Return a new client, starting its background thread and sending
+    /// requests with the `foursquare::Client` returned by `build`, which is
+    /// called on that thread. Use this to provide custom connection
+    /// options, a proxy or a custom `Transport`
+    pub fn with_client<B>(build: B) -> Result<Self>
+    where
+        B: FnOnce(&Handle) -> ::Client + Send + 'static,
+    {
+        Ok(Self { runtime: Runtime::start(build)? })
+    }
+
+    /// Return an interface to venue operations
+    pub fn venues(&self) -> Venues {
+        Venues { client: self }
+    }
+
+    fn execute<T, F>(&self, operation: F) -> ResponseFuture<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&venue::Venues) -> Future<Response<T>> + Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot {
+            result: None,
+            waker: None,
+        }));
+        let completed = slot.clone();
+        let sent = self.runtime.execute(operation, move |result| {
+            let mut slot = completed.lock().unwrap();
+            slot.result = Some(result);
+            if let Some(waker) = slot.waker.take() {
+                waker.wake()
+            }
+        });
+        if let Err(err) = sent {
+            slot.lock().unwrap().result = Some(Err(err));
+        }
+        ResponseFuture { slot }
+    }
+}
+
+struct Slot<T> {
+    result: Option<Result<Response<T>>>,
+    waker: Option<Waker>,
+}
+
+/// The eventual response of an operation
+pub struct ResponseFuture<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+
+impl<T> StdFuture for ResponseFuture<T> {
+    type Output = Result<Response<T>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut slot = self.slot.lock().unwrap();
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            _ => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// Venue operations. See `foursquare::venue::Venues` for details of each
+/// operation
+pub struct Venues<'a> {
+    client: &'a Client,
+}
+
+impl<'a> Venues<'a> {
+    /// Get all venue categories
+    pub fn categories(
+        &self,
+        options: &CategoriesOptions,
+    ) -> ResponseFuture<CategoriesResponse> {
+        let options = options.clone();
+        self.client.execute(move |venues| venues.categories(&options))
+    }
+
+    /// Get the tips for a single venue
+    pub fn tips<I>(
+        &self,
+        id: I,
+        options: &TipsOptions,
+    ) -> ResponseFuture<TipsResponse>
+    where
+        I: Into<String>,
+    {
+        let (id, options) = (id.into(), options.clone());
+        self.client.execute(move |venues| venues.tips(id, &options))
+    }
+
+    /// Get the hours for a single venue
+    pub fn hours<I>(
+        &self,
+        id: I,
+        options: &HoursOptions,
+    ) -> ResponseFuture<VenueHoursResponse>
+    where
+        I: Into<String>,
+    {
+        let (id, options) = (id.into(), options.clone());
+        self.client.execute(move |venues| venues.hours(id, &options))
+    }
+
+    /// Get the details for single venue
+    pub fn get<I>(
+        &self,
+        id: I,
+        options: &VenueDetailsOptions,
+    ) -> ResponseFuture<VenueResponse>
+    where
+        I: Into<String>,
+    {
+        let (id, options) = (id.into(), options.clone());
+        self.client.execute(move |venues| venues.get(id, &options))
+    }
+
+    /// Search for venues
+    pub fn search(
+        &self,
+        options: &SearchOptions,
+    ) -> ResponseFuture<SearchResponse> {
+        let options = options.clone();
+        self.client.execute(move |venues| venues.search(&options))
+    }
+
+    /// Type ahead suggestions
+    pub fn suggest(
+        &self,
+        options: &SuggestOptions,
+    ) -> ResponseFuture<SuggestResponse> {
+        let options = options.clone();
+        self.client.execute(move |venues| venues.suggest(&options))
+    }
+
+    /// Get venue recommendations in a target geography
+    pub fn recommendations(
+        &self,
+        options: &RecommendationsOptions,
+    ) -> ResponseFuture<RecommendationsResponse> {
+        let options = options.clone();
+        self.client.execute(
+            move |venues| venues.recommendations(&options),
+        )
+    }
+
+    /// Explore venues in a target geography
+    pub fn explore(
+        &self,
+        options: &ExploreOptions,
+    ) -> ResponseFuture<ExploreResponse> {
+        let options = options.clone();
+        self.client.execute(move |venues| venues.explore(&options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::{block_on, memory_client};
+
+    #[test]
+    fn awaits_responses() {
+        let fs = Client::with_client(memory_client).unwrap();
+        let hours = block_on(fs.venues().hours("1", &HoursOptions::default()))
+            .unwrap();
+        assert!(hours.response.hours.timeframes.is_empty());
+        assert!(
+            block_on(fs.venues().hours("2", &HoursOptions::default())).is_err()
+        )
+    }
+
+    #[test]
+    fn futures_are_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<Client>();
+        assert_send::<ResponseFuture<SearchResponse>>()
+    }
+}
@@ -0,0 +1,644 @@
+//! Command line interface for the foursquare api
+//!
+//! Credentials are read from the `--client-id` and `--client-secret` or
+//! `--oauth-token` flags, falling back on the `FS_CLIENT_ID`,
+//! `FS_CLIENT_SECRET` and `FS_OAUTH_TOKEN` environment variables.
+//!
+//! Requires the `tls` or `rustls` feature along with `cli`.
+
+#[cfg(not(any(feature = "tls", feature = "rustls")))]
+compile_error!("the cli feature requires the tls or rustls feature");
+
+extern crate clap;
+extern crate csv;
+#[macro_use(quick_main)]
+extern crate error_chain;
+extern crate foursquare;
+extern crate serde;
+extern crate serde_json;
+extern crate tokio_core;
+
+use std::env;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use serde::Serialize;
+use tokio_core::reactor::Core;
+
+use foursquare::{Client, Credentials, Error, Result};
+use foursquare::export::Record;
+use foursquare::venue::{CategoriesOptions, CategoryTree, ExploreOptions,
+                        Feature, HoursOptions, Intent, RecommendationsOptions,
+                        SearchOptions, SuggestOptions, TipsOptions, Venue,
+                        VenueDetailsOptions, VenueHours, VenueResults};
+
+const DEFAULT_VERSION: &str = "20170801";
+
+quick_main!(run);
+
+/// set builder fields from string flags
+macro_rules! strings {
+    ($matches:ident, $builder:ident, $($name:expr => $setter:ident),*) => {
+        $(
+            if let Some(value) = $matches.value_of($name) {
+                $builder.$setter(value.to_owned());
+            }
+        )*
+    };
+}
+
+/// set builder fields from flags parsed as another type
+macro_rules! parsed {
+    ($matches:ident, $builder:ident,
+     $($name:expr => $setter:ident: $ty:ty),*) => {
+        $(
+            if let Some(value) = parse::<$ty>($matches, $name)? {
+                $builder.$setter(value);
+            }
+        )*
+    };
+}
+
+/// set builder fields from boolean flags represented as 1 or 0
+macro_rules! flags {
+    ($matches:ident, $builder:ident, $($name:expr => $setter:ident),*) => {
+        $(
+            if $matches.is_present($name) {
+                $builder.$setter(1u16);
+            }
+        )*
+    };
+}
+
+fn opt<'a>(name: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    Arg::with_name(name).long(name).takes_value(true).help(help)
+}
+
+fn list<'a>(name: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    opt(name, help).multiple(true).use_delimiter(true)
+}
+
+fn flag<'a>(name: &'a str, help: &'a str) -> Arg<'a, 'a> {
+    Arg::with_name(name).long(name).help(help)
+}
+
+fn id<'a>() -> Arg<'a, 'a> {
+    Arg::with_name("id").required(true).help("Venue id")
+}
+
+fn location<'a>() -> Vec<Arg<'a, 'a>> {
+    vec![
+        opt("ll", "Latitude and longitude, e.g. 40.7,-74"),
+        opt("near", "A string naming a place in the world"),
+        opt("ll-acc", "Accuracy of latitude and longitude, in meters"),
+        opt("alt", "Altitude of the user's location, in meters"),
+        opt("alt-acc", "Accuracy of the user's altitude, in meters"),
+        opt("radius", "Radius to search within, in meters"),
+        opt("query", "A search term to be applied against venue names"),
+        opt("limit", "Number of results to return, up to 50"),
+        opt("locale", "Locale of the response, e.g. fr"),
+    ]
+}
+
+fn app<'a>() -> App<'a, 'a> {
+    App::new("foursquare")
+        .about("Command line interface for the foursquare api")
+        .version(env!("CARGO_PKG_VERSION"))
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .args(&[
+            opt("client-id", "Client id, defaults to FS_CLIENT_ID")
+                .global(true),
+            opt("client-secret", "Client secret, defaults to FS_CLIENT_SECRET")
+                .global(true),
+            opt("oauth-token", "User oauth token, defaults to FS_OAUTH_TOKEN")
+                .global(true),
+            opt("api-version", "Api version in YYYYMMDD format").global(true),
+            opt("output", "Output format")
+                .short("o")
+                .possible_values(&["json", "table", "csv"])
+                .default_value("json")
+                .global(true),
+        ])
+        .subcommand(
+            SubCommand::with_name("search")
+                .about("Search for venues")
+                .args(&location())
+                .args(&[
+                    opt("intent", "Intent in performing the search")
+                        .possible_values(
+                            &["checkin", "global", "browse", "match"],
+                        ),
+                    opt("sw", "South-west corner of a bounding box"),
+                    opt("ne", "North-east corner of a bounding box"),
+                    opt("category-id", "Comma separated category ids"),
+                    opt("url", "A third-party url to match venues against"),
+                    opt("provider-id", "Identifier for a known third party"),
+                    opt("linked-id", "Identifier used by the provider"),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("suggest")
+                .about("Type ahead venue suggestions")
+                .args(&location())
+                .args(&[
+                    opt("sw", "South-west corner of a bounding box"),
+                    opt("ne", "North-east corner of a bounding box"),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("explore")
+                .about("Explore venues in a target geography")
+                .args(&location())
+                .args(&[
+                    opt("section", "One of food, drinks, coffee, shops, arts, outdoors, sights, trending, nextVenues or topPicks"),
+                    opt("offset", "Used to page through results"),
+                    opt("novelty", "One of new or old"),
+                    opt("friend-visits", "One of visited or notvisited"),
+                    opt("time", "Pass any to retrieve results for any time of day"),
+                    opt("day", "Pass any to retrieve results for any day of the week"),
+                    flag("venue-photos", "Include a photo for each venue"),
+                    opt("last-venue", "A venue id to use with the nextVenues section"),
+                    flag("open-now", "Only include venues that are open now"),
+                    flag("sort-by-distance", "Sort results by distance"),
+                    opt("price", "Comma separated list of price points 1 through 4"),
+                    flag("saved", "Only include venues the user has saved"),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("recommend")
+                .about("Get venue recommendations in a target geography")
+                .args(&location())
+                .args(&[
+                    opt("intent", "One of food, breakfast, brunch, lunch, coffee, dinner, dessert, drinks, shopping, fun or sights"),
+                    list("prices", "Price tiers 1 through 4"),
+                    list("categories", "Categories to match after the query is applied"),
+                    opt("category-id", "Category to match when there is no query"),
+                    opt("offset", "Used to page through results"),
+                    list("features", "Required venue features")
+                        .possible_values(FEATURES),
+                    flag("open-now", "Only include venues that are open now"),
+                    flag("sort-by-distance", "Sort results by distance"),
+                    opt("personalization", "Personalize results for the user"),
+                    opt("local-day", "1 through 7 for Monday through Sunday"),
+                    opt("local-time", "Only return results open at this time, HH in 24-hr format"),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("venue")
+                .about("Get the details of a venue")
+                .args(&[id(), opt("locale", "Locale of the response")]),
+        )
+        .subcommand(
+            SubCommand::with_name("hours")
+                .about("Get the hours of a venue")
+                .args(&[id(), opt("locale", "Locale of the response")]),
+        )
+        .subcommand(
+            SubCommand::with_name("tips")
+                .about("Get the tips for a venue")
+                .args(&[
+                    id(),
+                    opt("sort", "One of friends, recent or popular"),
+                    opt("limit", "Number of results to return, up to 500"),
+                    opt("offset", "Used to page through results"),
+                    opt("locale", "Locale of the response"),
+                ]),
+        )
+        .subcommand(
+            SubCommand::with_name("categories")
+                .about("Get all venue categories")
+                .arg(opt("locale", "Locale of the response")),
+        )
+}
+
+const FEATURES: &[&str] = &[
+    "credit-cards",
+    "reservations",
+    "delivery",
+    "take-out",
+    "wifi",
+    "outdoor-seating",
+    "liked",
+    "recently-opened",
+    "not-chain",
+    "online-reservations",
+    "dog-friendly",
+    "parking",
+    "happy-hour",
+];
+
+fn feature(name: &str) -> Result<Feature> {
+    Ok(match name {
+        "credit-cards" => Feature::TakesCreditCards,
+        "reservations" => Feature::TakesReservations,
+        "delivery" => Feature::OffersDelivery,
+        "take-out" => Feature::OffersTakeOut,
+        "wifi" => Feature::Wifi,
+        "outdoor-seating" => Feature::OutdoorSeating,
+        "liked" => Feature::Liked,
+        "recently-opened" => Feature::RecentlyOpened,
+        "not-chain" => Feature::NotChain,
+        "online-reservations" => Feature::OnlineReservations,
+        "dog-friendly" => Feature::DogFriendly,
+        "parking" => Feature::ParkingLot,
+        "happy-hour" => Feature::HappyHour,
+        _ => return Err(invalid(name, "features")),
+    })
+}
+
+fn intent(name: &str) -> Result<Intent> {
+    Ok(match name {
+        "checkin" => Intent::Checkin,
+        "global" => Intent::Global,
+        "browse" => Intent::Browse,
+        "match" => Intent::Match,
+        _ => return Err(invalid(name, "intent")),
+    })
+}
+
+fn invalid(value: &str, name: &str) -> Error {
+    format!("invalid value '{}' for --{}", value, name).into()
+}
+
+fn parse<T>(matches: &ArgMatches, name: &str) -> Result<Option<T>>
+where
+    T: FromStr,
+{
+    match matches.value_of(name) {
+        Some(value) => {
+            value.parse::<T>().map(Some).map_err(|_| invalid(value, name))
+        }
+        _ => Ok(None),
+    }
+}
+
+fn values(matches: &ArgMatches, name: &str) -> Option<Vec<String>> {
+    matches.values_of(name).map(
+        |values| values.map(String::from).collect(),
+    )
+}
+
+fn credentials(matches: &ArgMatches) -> Result<Credentials> {
+    let arg = |name: &str, var: &str| {
+        matches.value_of(name).map(String::from).or_else(
+            || env::var(var).ok(),
+        )
+    };
+    if let Some(token) = arg("oauth-token", "FS_OAUTH_TOKEN") {
+        return Ok(Credentials::user(token));
+    }
+    match (
+        arg("client-id", "FS_CLIENT_ID"),
+        arg("client-secret", "FS_CLIENT_SECRET"),
+    ) {
+        (Some(id), Some(secret)) => Ok(Credentials::client(id, secret)),
+        _ => Err(
+            "missing FS_CLIENT_ID and/or FS_CLIENT_SECRET or FS_OAUTH_TOKEN"
+                .into(),
+        ),
+    }
+}
+
+/// tabular representation of a response
+struct Table {
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+fn cell<T>(value: &Option<T>) -> String
+where
+    T: Display,
+{
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+fn venues<'a, I>(venues: I) -> Table
+where
+    I: IntoIterator<Item = &'a Venue>,
+{
+    Table {
+        headers: vec![
+            "id",
+            "name",
+            "lat",
+            "lng",
+            "address",
+            "formatted_address",
+            "city",
+            "cc",
+            "category_id",
+            "category_name",
+            "category_ids",
+            "rating",
+            "rating_signals",
+            "price_tier",
+            "url",
+            "verified",
+        ],
+        rows: venues
+            .into_iter()
+            .map(Record::from)
+            .map(|record| {
+                vec![
+                    record.id,
+                    record.name,
+                    record.lat.to_string(),
+                    record.lng.to_string(),
+                    cell(&record.address),
+                    cell(&record.formatted_address),
+                    cell(&record.city),
+                    cell(&record.cc),
+                    cell(&record.category_id),
+                    cell(&record.category_name),
+                    record.category_ids,
+                    cell(&record.rating),
+                    cell(&record.rating_signals),
+                    cell(&record.price_tier),
+                    cell(&record.url),
+                    cell(&record.verified),
+                ]
+            })
+            .collect(),
+    }
+}
+
+fn hours(kind: &str, hours: &VenueHours) -> Vec<Vec<String>> {
+    hours
+        .timeframes
+        .iter()
+        .flat_map(|frame| {
+            let days = frame
+                .days
+                .iter()
+                .map(u16::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            frame
+                .open
+                .iter()
+                .map(|window| {
+                    vec![
+                        kind.to_owned(),
+                        days.clone(),
+                        window.start.clone(),
+                        window.end.clone(),
+                    ]
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn render<T>(matches: &ArgMatches, response: &T, table: Table) -> Result<()>
+where
+    T: Serialize,
+{
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    match matches.value_of("output") {
+        Some("table") => {
+            let widths = table
+                .headers
+                .iter()
+                .enumerate()
+                .map(|(i, header)| {
+                    table.rows.iter().map(|row| row[i].chars().count()).fold(
+                        header.len(),
+                        ::std::cmp::max,
+                    )
+                })
+                .collect::<Vec<_>>();
+            let line = |out: &mut Write, row: Vec<&str>| -> io::Result<()> {
+                let cells = row.iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:1$}", cell, width))
+                    .collect::<Vec<_>>();
+                writeln!(out, "{}", cells.join("  ").trim_right())
+            };
+            let rule =
+                widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>();
+            line(&mut out, table.headers.clone())?;
+            line(&mut out, rule.iter().map(String::as_str).collect())?;
+            for row in &table.rows {
+                line(&mut out, row.iter().map(String::as_str).collect())?;
+            }
+        }
+        Some("csv") => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(&table.headers)?;
+            for row in &table.rows {
+                writer.write_record(row)?;
+            }
+            writer.flush()?;
+        }
+        _ => {
+            serde_json::to_writer_pretty(&mut out, response)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+fn run() -> Result<()> {
+    let matches = app().get_matches();
+    let mut core = Core::new()?;
+    let client = Client::new(
+        matches.value_of("api-version").unwrap_or(DEFAULT_VERSION),
+        credentials(&matches)?,
+        &core.handle(),
+    );
+    let venues_api = client.venues();
+    match matches.subcommand() {
+        ("search", Some(args)) => {
+            let mut options = SearchOptions::builder();
+            strings!(args, options,
+                "ll" => ll, "near" => near, "query" => query, "sw" => sw,
+                "ne" => ne, "category-id" => category_id, "url" => url,
+                "provider-id" => provider_id, "linked-id" => linked_id,
+                "locale" => locale);
+            parsed!(args, options,
+                "radius" => radius: u32, "limit" => limit: u32,
+                "ll-acc" => ll_acc: f64, "alt" => alt: u32,
+                "alt-acc" => alt_acc: f64);
+            if let Some(value) = args.value_of("intent") {
+                options.intent(intent(value)?);
+            }
+            let res = core.run(venues_api.search(&options.build()?))?;
+            render(&matches, &res, venues(res.response.venues()))
+        }
+        ("suggest", Some(args)) => {
+            let mut options = SuggestOptions::builder();
+            strings!(args, options,
+                "ll" => ll, "near" => near, "query" => query, "sw" => sw,
+                "ne" => ne, "locale" => locale);
+            parsed!(args, options,
+                "radius" => radius: u32, "limit" => limit: u32,
+                "ll-acc" => ll_acc: f64, "alt" => alt: u32,
+                "alt-acc" => alt_acc: f64);
+            let res = core.run(venues_api.suggest(&options.build()?))?;
+            render(&matches, &res, venues(res.response.venues()))
+        }
+        ("explore", Some(args)) => {
+            let mut options = ExploreOptions::builder();
+            strings!(args, options,
+                "ll" => ll, "near" => near, "query" => query,
+                "section" => section, "novelty" => novelty,
+                "friend-visits" => friend_visits, "time" => time,
+                "day" => day, "last-venue" => last_venue, "price" => price,
+                "locale" => locale);
+            parsed!(args, options,
+                "radius" => radius: u32, "limit" => limit: u32,
+                "offset" => offset: u32, "ll-acc" => ll_acc: f64,
+                "alt" => alt: u32, "alt-acc" => alt_acc: f64);
+            flags!(args, options,
+                "venue-photos" => venue_photos, "open-now" => open_now,
+                "sort-by-distance" => sort_by_distance, "saved" => saved);
+            let res = core.run(venues_api.explore(&options.build()?))?;
+            render(&matches, &res, venues(res.response.venues()))
+        }
+        ("recommend", Some(args)) => {
+            let mut options = RecommendationsOptions::builder();
+            strings!(args, options,
+                "ll" => ll, "near" => near, "query" => query,
+                "intent" => intent, "category-id" => category_id,
+                "personalization" => personalization,
+                "local-day" => local_day, "local-time" => local_time,
+                "locale" => locale);
+            parsed!(args, options,
+                "radius" => radius: u32, "limit" => limit: u32,
+                "offset" => offset: u32, "ll-acc" => ll_acc: f64,
+                "alt" => alt: u32, "alt-acc" => alt_acc: f64);
+            if let Some(prices) = values(args, "prices") {
+                options.prices(
+                    prices
+                        .iter()
+                        .map(|price| {
+                            price.parse::<u16>().map_err(|_| {
+                                format!("invalid price '{}'", price).into()
+                            })
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                );
+            }
+            if let Some(categories) = values(args, "categories") {
+                options.categories(categories);
+            }
+            if let Some(features) = values(args, "features") {
+                options.features(
+                    features
+                        .iter()
+                        .map(|f| feature(f))
+                        .collect::<Result<Vec<_>>>()?,
+                );
+            }
+            if args.is_present("open-now") {
+                options.open_now(true);
+            }
+            if args.is_present("sort-by-distance") {
+                options.sort_by_distance(true);
+            }
+            let res =
+                core.run(venues_api.recommendations(&options.build()?))?;
+            render(&matches, &res, venues(res.response.venues()))
+        }
+        ("venue", Some(args)) => {
+            let mut options = VenueDetailsOptions::builder();
+            strings!(args, options, "locale" => locale);
+            let res = core.run(venues_api.get(
+                args.value_of("id").unwrap_or_default(),
+                &options.build()?,
+            ))?;
+            render(&matches, &res, venues(vec![&res.response.venue]))
+        }
+        ("hours", Some(args)) => {
+            let mut options = HoursOptions::builder();
+            strings!(args, options, "locale" => locale);
+            let res = core.run(venues_api.hours(
+                args.value_of("id").unwrap_or_default(),
+                &options.build()?,
+            ))?;
+            let mut rows = hours("hours", &res.response.hours);
+            rows.extend(hours("popular", &res.response.popular));
+            render(
+                &matches,
+                &res,
+                Table {
+                    headers: vec!["type", "days", "start", "end"],
+                    rows,
+                },
+            )
+        }
+        ("tips", Some(args)) => {
+            let mut options = TipsOptions::builder();
+            strings!(args, options, "sort" => sort, "locale" => locale);
+            parsed!(args, options,
+                "limit" => limit: u32, "offset" => offset: u32);
+            let res = core.run(venues_api.tips(
+                args.value_of("id").unwrap_or_default(),
+                &options.build()?,
+            ))?;
+            let rows = res.response
+                .tips
+                .items
+                .iter()
+                .map(|tip| {
+                    vec![
+                        tip.id.clone(),
+                        tip.text.clone(),
+                        tip.canonical_url.clone(),
+                    ]
+                })
+                .collect();
+            render(
+                &matches,
+                &res,
+                Table {
+                    headers: vec!["id", "text", "canonical_url"],
+                    rows,
+                },
+            )
+        }
+        ("categories", Some(args)) => {
+            let mut options = CategoriesOptions::builder();
+            strings!(args, options, "locale" => locale);
+            let res = core.run(venues_api.categories(&options.build()?))?;
+            let tree = CategoryTree::new(res.response.categories.clone());
+            let rows = tree.flatten()
+                .into_iter()
+                .map(|category| {
+                    vec![
+                        category.id.clone(),
+                        category.name.clone(),
+                        category.plural_name.clone(),
+                        tree.parent(&category.id)
+                            .map(|parent| parent.id.clone())
+                            .unwrap_or_default(),
+                    ]
+                })
+                .collect();
+            render(
+                &matches,
+                &res,
+                Table {
+                    headers: vec!["id", "name", "plural_name", "parent_id"],
+                    rows,
+                },
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_every_feature() {
+        assert!(FEATURES.iter().all(|name| feature(name).is_ok()));
+        assert!(feature("happy-hours").is_err());
+        assert!(intent("checkins").is_err())
+    }
+}
@@ -0,0 +1,206 @@
+//! A synchronous interface for interacting with Foursquare API
+//!
+//! A `blocking::Client` owns a background thread running its own reactor,
+//! so callers need not manage a `tokio_core::reactor::Core`. Operations
+//! block the calling thread until their response arrives. Clients may be
+//! cloned and shared across threads; the background thread exits when the
+//! last clone is dropped.
+//!
+//! ```no_run
+//! extern crate foursquare;
+//!
+//! use foursquare::Credentials;
+//! use foursquare::blocking::Client;
+//! use foursquare::venue::SearchOptions;
+//!
+//! fn main() {
+//!   let fs = Client::new(
+//!     "YYYYMMDD",
+//!     Credentials::client("client_id", "client_secret")
+//!   ).expect("runtime fail");
+//!   let response = fs.venues().search(
+//!     &SearchOptions::builder().near("Brooklyn, NY").build().unwrap()
+//!   ).unwrap();
+//!   for venue in response.response.venues {
+//!     println!("{}", venue.name);
+//!   }
+//! }
+//! ```
+
+use futures::Future as StdFuture;
+use futures::sync::oneshot;
+use tokio_core::reactor::Handle;
+
+use {Credentials, ErrorKind, Future, Response, Result};
+use runtime::Runtime;
+use venue::{self, CategoriesOptions, CategoriesResponse, ExploreOptions,
+            ExploreResponse, HoursOptions, RecommendationsOptions,
+            RecommendationsResponse, SearchOptions, SearchResponse,
+            SuggestOptions, SuggestResponse, TipsOptions, TipsResponse,
+            VenueDetailsOptions, VenueHoursResponse, VenueResponse};
+
+/// Entry point interface for synchronously interacting with Foursquare API
+#[derive(Clone)]
+pub struct Client {
+    runtime: Runtime,
+}
+
+impl Client {
+    /// Return a new client, starting its background thread
+    ///
+    /// version should be in `YYYYMMDD` format
+    pub fn new<V>(version: V, credentials: Credentials) -> Result<Self>
+    where
+        V: Into<String>,
+    {
+        let version = version.into();
+        Self::with_client(
+            move |handle| ::Client::new(version, credentials, handle),
+        )
+    }
+
+    /// Return a new client, starting its background thread and sending
+    /// requests with the `foursquare::Client` returned by `build`, which is
+    /// called on that thread. Use this to provide custom connection
+    /// options, a proxy or a custom `Transport`
+    pub fn with_client<B>(build: B) -> Result<Self>
+    where
+        B: FnOnce(&Handle) -> ::Client + Send + 'static,
+    {
+        Ok(Self { runtime: Runtime::start(build)? })
+    }
+
+    /// Return an interface to venue operations
+    pub fn venues(&self) -> Venues {
+        Venues { client: self }
+    }
+
+    fn execute<T, F>(&self, operation: F) -> Result<Response<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&venue::Venues) -> Future<Response<T>> + Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.runtime.execute(
+            operation,
+            move |result| drop(tx.send(result)),
+        )?;
+        match rx.wait() {
+            Ok(result) => result,
+            _ => Err(ErrorKind::RuntimeShutdown.into()),
+        }
+    }
+}
+
+/// Synchronous venue operations. See `foursquare::venue::Venues` for
+/// details of each operation
+pub struct Venues<'a> {
+    client: &'a Client,
+}
+
+impl<'a> Venues<'a> {
+    /// Get all venue categories
+    pub fn categories(
+        &self,
+        options: &CategoriesOptions,
+    ) -> Result<Response<CategoriesResponse>> {
+        let options = options.clone();
+        self.client.execute(move |venues| venues.categories(&options))
+    }
+
+    /// Get the tips for a single venue
+    pub fn tips<I>(
+        &self,
+        id: I,
+        options: &TipsOptions,
+    ) -> Result<Response<TipsResponse>>
+    where
+        I: Into<String>,
+    {
+        let (id, options) = (id.into(), options.clone());
+        self.client.execute(move |venues| venues.tips(id, &options))
+    }
+
+    /// Get the hours for a single venue
+    pub fn hours<I>(
+        &self,
+        id: I,
+        options: &HoursOptions,
+    ) -> Result<Response<VenueHoursResponse>>
+    where
+        I: Into<String>,
+    {
+        let (id, options) = (id.into(), options.clone());
+        self.client.execute(move |venues| venues.hours(id, &options))
+    }
+
+    /// Get the details for single venue
+    pub fn get<I>(
+        &self,
+        id: I,
+        options: &VenueDetailsOptions,
+    ) -> Result<Response<VenueResponse>>
+    where
+        I: Into<String>,
+    {
+        let (id, options) = (id.into(), options.clone());
+        self.client.execute(move |venues| venues.get(id, &options))
+    }
+
+    /// Search for venues
+    pub fn search(
+        &self,
+        options: &SearchOptions,
+    ) -> Result<Response<SearchResponse>> {
+        let options = options.clone();
+        self.client.execute(move |venues| venues.search(&options))
+    }
+
+    /// Type ahead suggestions
+    pub fn suggest(
+        &self,
+        options: &SuggestOptions,
+    ) -> Result<Response<SuggestResponse>> {
+        let options = options.clone();
+        self.client.execute(move |venues| venues.suggest(&options))
+    }
+
+    /// Get venue recommendations in a target geography
+    pub fn recommendations(
+        &self,
+        options: &RecommendationsOptions,
+    ) -> Result<Response<RecommendationsResponse>> {
+        let options = options.clone();
+        self.client.execute(
+            move |venues| venues.recommendations(&options),
+        )
+    }
+
+    /// Explore venues in a target geography
+    pub fn explore(
+        &self,
+        options: &ExploreOptions,
+    ) -> Result<Response<ExploreResponse>> {
+        let options = options.clone();
+        self.client.execute(move |venues| venues.explore(&options))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testing::memory_client;
+
+    #[test]
+    fn runs_custom_clients() {
+        let fs = Client::with_client(memory_client).unwrap();
+        let hours = fs.venues().hours("1", &HoursOptions::default()).unwrap();
+        assert!(hours.response.hours.timeframes.is_empty())
+    }
+
+    #[test]
+    fn client_is_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Client>()
+    }
+}
@@ -0,0 +1,152 @@
+//! Response compression
+//!
+//! With the `compression` feature, requests advertise gzip and deflate
+//! support with an `Accept-Encoding` header and compressed response bodies
+//! are decompressed before they are deserialized, whichever `Transport`
+//! a `Client` sends requests with.
+
+use std::io::Read;
+
+use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+
+use Result;
+use transport::Response;
+
+/// The `Accept-Encoding` header value sent with requests
+pub(crate) const ACCEPT_ENCODING: &str = "gzip, deflate";
+
+/// Decompress a response body according to its `Content-Encoding` header,
+/// removing the header from the returned response
+pub(crate) fn decode(mut response: Response) -> Result<Response> {
+    let encoding = match response.header("Content-Encoding") {
+        Some(encoding) => encoding.trim().to_lowercase(),
+        _ => return Ok(response),
+    };
+    let mut body = Vec::new();
+    match encoding.as_str() {
+        "gzip" | "x-gzip" => {
+            GzDecoder::new(&response.body[..]).read_to_end(&mut body)?;
+        }
+        // servers disagree on whether deflate bodies carry a zlib wrapper,
+        // so fall back on a raw deflate stream
+        "deflate" => {
+            if ZlibDecoder::new(&response.body[..])
+                .read_to_end(&mut body)
+                .is_err()
+            {
+                body.clear();
+                DeflateDecoder::new(&response.body[..]).read_to_end(
+                    &mut body,
+                )?;
+            }
+        }
+        "identity" => return Ok(response),
+        other => {
+            return Err(format!("unsupported content encoding {}", other).into())
+        }
+    }
+    debug!(
+        "decoded {} {} byte body to {} bytes",
+        encoding,
+        response.body.len(),
+        body.len()
+    );
+    response.headers.retain(|&(ref name, _)| {
+        !name.eq_ignore_ascii_case("Content-Encoding")
+    });
+    response.body = body;
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    use flate2::Compression;
+    use flate2::write::{DeflateEncoder, GzEncoder, ZlibEncoder};
+    use hyper::Client as HyperClient;
+    use tokio_core::reactor::Core;
+
+    use {Client, Credentials};
+    use venue::HoursOptions;
+
+    const HOURS: &str = r#"{"meta": {"code": 200, "requestId": "1"},
+        "response": {"hours": {"timeframes": []},
+                     "popular": {"timeframes": []}}}"#;
+
+    fn encoded(encoding: &str, body: Vec<u8>) -> Response {
+        Response {
+            status: 200,
+            headers: vec![("Content-Encoding".into(), encoding.into())],
+            body,
+        }
+    }
+
+    #[test]
+    fn decodes_bodies() {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(b"gzip").unwrap();
+        let mut zlib = ZlibEncoder::new(Vec::new(), Compression::default());
+        zlib.write_all(b"zlib").unwrap();
+        let mut deflate = DeflateEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(b"deflate").unwrap();
+
+        let gzip = decode(encoded("gzip", gzip.finish().unwrap())).unwrap();
+        assert_eq!(gzip.body, b"gzip");
+        assert!(gzip.header("Content-Encoding").is_none());
+        let zlib = decode(encoded("deflate", zlib.finish().unwrap())).unwrap();
+        assert_eq!(zlib.body, b"zlib");
+        let deflate = decode(encoded("Deflate", deflate.finish().unwrap())).unwrap();
+        assert_eq!(deflate.body, b"deflate");
+        assert!(decode(encoded("br", Vec::new())).is_err())
+    }
+
+    #[test]
+    fn decompresses_server_responses() {
+        let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+        gzip.write_all(HOURS.as_bytes()).unwrap();
+        let body = gzip.finish().unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut headers = Vec::new();
+            {
+                let mut reader = BufReader::new(&stream);
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    headers.push(line.trim().to_lowercase());
+                }
+            }
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Encoding: gzip\r\n\
+                 Content-Length: {}\r\n\r\n",
+                body.len()
+            ).unwrap();
+            stream.write_all(&body).unwrap();
+            headers
+        });
+
+        let mut core = Core::new().unwrap();
+        let mut fs = Client::custom(
+            "20170801",
+            Credentials::user("token"),
+            HyperClient::new(&core.handle()),
+        );
+        fs.host = format!("http://127.0.0.1:{}", port);
+        let hours = core.run(fs.venues().hours("1", &HoursOptions::default()))
+            .unwrap();
+        assert!(hours.response.hours.timeframes.is_empty());
+        let headers = server.join().unwrap();
+        assert!(headers.contains(&"accept-encoding: gzip, deflate".into()))
+    }
+}
@@ -0,0 +1,171 @@
+//! Connector and keep-alive tuning
+//!
+//! `Client::new` uses `ConnectionOptions::default()`. Use
+//! `Client::with_options` to tune DNS resolver threads, whether and for how
+//! long idle connections are kept alive, TCP socket options and proxy
+//! settings.
+//!
+//! HTTP/2 multiplexing and a limit on the number of idle connections kept
+//! per host are not implemented yet. hyper 0.11 has no HTTP/2 client, so
+//! requests are always sent over HTTP/1.1, and no per host idle limit, so
+//! both await an upgrade to a hyper release which supports them.
+//!
+//! ```no_run
+//! extern crate foursquare;
+//! extern crate tokio_core;
+//!
+//! use std::time::Duration;
+//! use tokio_core::reactor::Core;
+//! use foursquare::{Client, Credentials};
+//! use foursquare::connection::ConnectionOptions;
+//!
+//! fn main() {
+//!   let core = Core::new().expect("reactor fail");
+//!   let options = ConnectionOptions::builder()
+//!     .dns_threads(8usize)
+//!     .idle_timeout(Duration::from_secs(30))
+//!     .nodelay(true)
+//!     .build()
+//!     .unwrap();
+//!   let fs = Client::with_options(
+//!     "YYYYMMDD",
+//!     Credentials::client("client_id", "client_secret"),
+//!     &core.handle(),
+//!     &options
+//!   ).unwrap();
+//! }
+//! ```
+
+#[cfg(feature = "tls")]
+use std::io;
+use std::time::Duration;
+
+#[cfg(feature = "tls")]
+use futures::Future as StdFuture;
+#[cfg(feature = "tls")]
+use hyper::Uri;
+use hyper::client::Config;
+#[cfg(feature = "tls")]
+use tokio_core::net::TcpStream;
+#[cfg(feature = "tls")]
+use tokio_service::Service;
+
+use proxy::Proxy;
+
+/// Connection options.
+///
+/// Use ConnectionOptions::builder() interface to construct these
+#[derive(Debug, Clone, Builder)]
+#[builder(setter(into))]
+pub struct ConnectionOptions {
+    /// The number of threads used to resolve host names
+    #[builder(default = "4")]
+    pub dns_threads: usize,
+    /// Reuse connections between requests
+    #[builder(default = "true")]
+    pub keep_alive: bool,
+    /// How long an unused pooled connection is kept open. `None` keeps idle
+    /// connections open indefinitely
+    #[builder(default = "Some(Duration::from_secs(90))")]
+    pub idle_timeout: Option<Duration>,
+    /// Enables `SO_KEEPALIVE` on sockets with the provided duration
+    #[builder(default)]
+    pub tcp_keepalive: Option<Duration>,
+    /// Enables `TCP_NODELAY` on sockets, disabling Nagle's algorithm
+    #[builder(default)]
+    pub nodelay: bool,
+    /// An HTTP proxy to tunnel connections through. Requires the `tls`
+    /// feature, as rustls clients can't be proxied
+    #[builder(default)]
+    pub proxy: Option<Proxy>,
+}
+
+impl ConnectionOptions {
+    pub fn builder() -> ConnectionOptionsBuilder {
+        ConnectionOptionsBuilder::default()
+    }
+
+    /// Apply keep-alive settings to a hyper client configuration
+    pub(crate) fn configure<C, B>(&self, config: Config<C, B>) -> Config<C, B> {
+        config.keep_alive(self.keep_alive).keep_alive_timeout(
+            self.idle_timeout,
+        )
+    }
+}
+
+impl Default for ConnectionOptions {
+    fn default() -> Self {
+        ConnectionOptions::builder().build().unwrap()
+    }
+}
+
+/// A connector setting `TCP_NODELAY` on the connections made by another
+/// connector
+#[cfg(feature = "tls")]
+#[derive(Debug, Clone)]
+pub(crate) struct Nodelay<C> {
+    inner: C,
+    nodelay: bool,
+}
+
+#[cfg(feature = "tls")]
+impl<C> Nodelay<C> {
+    pub(crate) fn new(inner: C, nodelay: bool) -> Self {
+        Self { inner, nodelay }
+    }
+}
+
+#[cfg(feature = "tls")]
+impl<C> Service for Nodelay<C>
+where
+    C: Service<Request = Uri, Response = TcpStream, Error = io::Error>,
+    C::Future: 'static,
+{
+    type Request = Uri;
+    type Response = TcpStream;
+    type Error = io::Error;
+    type Future = Box<StdFuture<Item = TcpStream, Error = io::Error>>;
+
+    fn call(&self, uri: Uri) -> Self::Future {
+        let nodelay = self.nodelay;
+        Box::new(self.inner.call(uri).and_then(move |stream| {
+            stream.set_nodelay(nodelay)?;
+            Ok(stream)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "tls")]
+    use std::net::TcpListener;
+
+    #[cfg(feature = "tls")]
+    use hyper::client::HttpConnector;
+    #[cfg(feature = "tls")]
+    use tokio_core::reactor::Core;
+
+    #[test]
+    fn default_options() {
+        let options = ConnectionOptions::default();
+        assert_eq!(options.dns_threads, 4);
+        assert!(options.keep_alive);
+        assert_eq!(options.idle_timeout, Some(Duration::from_secs(90)));
+        assert!(!options.nodelay);
+        assert!(options.proxy.is_none())
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn sets_nodelay() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("http://{}", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let mut core = Core::new().unwrap();
+        let connector = Nodelay::new(HttpConnector::new(1, &core.handle()), true);
+        let stream = core.run(connector.call(uri)).unwrap();
+        assert!(stream.nodelay().unwrap())
+    }
+}
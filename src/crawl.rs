@@ -0,0 +1,544 @@
+//! Exhaustive venue collection within an area
+//!
+//! `Venues::search` returns at most 50 venues. A `Crawl` collects every
+//! venue in a bounding box by searching with `intent=browse` and splitting
+//! any cell whose results are saturated into quadrants, which are then
+//! searched in turn.
+//!
+//! ```no_run
+//! extern crate foursquare;
+//! extern crate futures;
+//! extern crate tokio_core;
+//!
+//! use futures::Stream;
+//! use tokio_core::reactor::Core;
+//! use foursquare::{Client, Credentials};
+//! use foursquare::crawl::{Crawl, CrawlOptions};
+//! use foursquare::geo::BoundingBox;
+//! use foursquare::venue::Coords;
+//!
+//! fn main() {
+//!   let mut core = Core::new().expect("reactor fail");
+//!   let fs = Client::new(
+//!     "YYYYMMDD",
+//!     Credentials::client("client_id", "client_secret"),
+//!     &core.handle()
+//!   );
+//!   let crawl = Crawl::new(
+//!     fs.venues(),
+//!     BoundingBox::new(
+//!       Coords::new(40.70, -74.02),
+//!       Coords::new(40.75, -73.97)
+//!     ),
+//!     CrawlOptions::builder().concurrency(2usize).build().unwrap()
+//!   );
+//!   core.run(crawl.for_each(|venue| {
+//!     println!("{}", venue.name);
+//!     Ok(())
+//!   })).unwrap();
+//! }
+//! ```
+
+// https://github.com/colin-kiegel/rust-derive-builder/issues/104
+#![allow(unused_mut)]
+use std::collections::{BTreeSet, VecDeque};
+
+use futures::{Async, Future as StdFuture, Poll, Stream};
+
+use {Error, Future, Response, Result};
+use geo::{BoundingBox, Position};
+use venue::{Coords, Intent, SearchOptions, SearchResponse, Venue, Venues};
+
+/// The most venues a single search returns
+const SEARCH_LIMIT: usize = 50;
+
+/// The largest area, in square meters, the api supports for bounding box
+/// searches
+const MAX_CELL_AREA: f64 = 10_000_000_000.0;
+
+/// Crawl options.
+///
+/// Use CrawlOptions::builder() interface to construct these
+#[derive(Debug, Clone, Builder)]
+#[builder(setter(into))]
+pub struct CrawlOptions {
+    /// Category ids to limit results to. Top-level categories include all
+    /// of their sub-categories
+    #[builder(default)]
+    pub categories: Option<Vec<String>>,
+    /// A search term to be applied against venue names
+    #[builder(default)]
+    pub query: Option<String>,
+    /// The maximum number of searches in flight at once
+    #[builder(default = "4")]
+    pub concurrency: usize,
+    /// The maximum number of times a saturated cell may be split. Cells at
+    /// this depth are not split further, and their remaining venues missed
+    #[builder(default = "16")]
+    pub max_depth: u8,
+    /// [Internationalization](https://developer.foursquare.com/docs/api/configuration/internationalization)
+    #[builder(default)]
+    pub locale: Option<String>,
+}
+
+impl CrawlOptions {
+    pub fn builder() -> CrawlOptionsBuilder {
+        CrawlOptionsBuilder::default()
+    }
+}
+
+impl Default for CrawlOptions {
+    fn default() -> Self {
+        CrawlOptions::builder().build().unwrap()
+    }
+}
+
+/// An area remaining to be searched
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cell {
+    pub bounds: BoundingBox,
+    /// The number of times this cell's ancestors were split because their
+    /// results were saturated. Splits of areas too large to search are not
+    /// counted
+    pub depth: u8,
+}
+
+/// The state of a crawl, sufficient to resume it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Cells not yet searched, including those in flight
+    pub pending: Vec<Cell>,
+    /// Ids of venues already received
+    pub seen: BTreeSet<String>,
+    /// Venues received but not yet yielded by the stream
+    #[serde(default)]
+    pub buffered: Vec<Venue>,
+}
+
+/// A stream of the distinct venues within an area
+pub struct Crawl {
+    venues: Venues,
+    options: CrawlOptions,
+    pending: VecDeque<Cell>,
+    in_flight: Vec<(Cell, Future<Response<SearchResponse>>)>,
+    buffered: VecDeque<Venue>,
+    seen: BTreeSet<String>,
+}
+
+impl Crawl {
+    /// Return a new crawl of the provided area
+    pub fn new(
+        venues: Venues,
+        bounds: BoundingBox,
+        options: CrawlOptions,
+    ) -> Self {
+        Self::resume(
+            venues,
+            Checkpoint {
+                pending: vec![Cell { bounds, depth: 0 }],
+                ..Default::default()
+            },
+            options,
+        )
+    }
+
+    /// Return a crawl resuming from a checkpoint
+    pub fn resume(
+        venues: Venues,
+        checkpoint: Checkpoint,
+        options: CrawlOptions,
+    ) -> Self {
+        Self {
+            venues,
+            options,
+            pending: checkpoint.pending.into_iter().collect(),
+            in_flight: Vec::new(),
+            buffered: checkpoint.buffered.into_iter().collect(),
+            seen: checkpoint.seen,
+        }
+    }
+
+    /// Return the current state of the crawl
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            pending: self.in_flight
+                .iter()
+                .map(|&(ref cell, _)| cell.clone())
+                .chain(self.pending.iter().cloned())
+                .collect(),
+            seen: self.seen.clone(),
+            buffered: self.buffered.iter().cloned().collect(),
+        }
+    }
+
+    fn search(&self, cell: &Cell) -> Result<Future<Response<SearchResponse>>> {
+        let mut options = SearchOptions::builder();
+        options
+            .intent(Intent::Browse)
+            .sw(cell.bounds.sw.to_string())
+            .ne(cell.bounds.ne.to_string())
+            .limit(SEARCH_LIMIT as u32);
+        if let Some(ref categories) = self.options.categories {
+            options.category_id(categories.join(","));
+        }
+        if let Some(ref query) = self.options.query {
+            options.query(query.clone());
+        }
+        if let Some(ref locale) = self.options.locale {
+            options.locale(locale.clone());
+        }
+        Ok(self.venues.search(&options.build()?))
+    }
+
+    fn handle(&mut self, cell: Cell, response: SearchResponse) {
+        if response.venues.len() >= SEARCH_LIMIT {
+            if cell.depth < self.options.max_depth {
+                self.pending.extend(split(&cell, cell.depth.saturating_add(1)));
+            } else {
+                warn!(
+                    "cell {:?} is saturated at max depth {}, venues beyond \
+                     the first {} may be missing",
+                    cell.bounds,
+                    cell.depth,
+                    SEARCH_LIMIT
+                )
+            }
+        }
+        for venue in response.venues {
+            if cell.bounds.contains(&venue) &&
+                self.seen.insert(venue.id.clone())
+            {
+                self.buffered.push_back(venue)
+            }
+        }
+    }
+}
+
+impl Stream for Crawl {
+    type Item = Venue;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Venue>, Error> {
+        loop {
+            if let Some(venue) = self.buffered.pop_front() {
+                return Ok(Async::Ready(Some(venue)));
+            }
+            while self.in_flight.len() < self.options.concurrency.max(1) {
+                match self.pending.pop_front() {
+                    Some(cell) => {
+                        if area(&cell.bounds) > MAX_CELL_AREA {
+                            // too large to search, split without searching
+                            self.pending.extend(split(&cell, cell.depth));
+                            continue;
+                        }
+                        let search = match self.search(&cell) {
+                            Ok(search) => search,
+                            Err(err) => {
+                                self.pending.push_front(cell);
+                                return Err(err);
+                            }
+                        };
+                        self.in_flight.push((cell, search))
+                    }
+                    _ => break,
+                }
+            }
+            if self.in_flight.is_empty() {
+                return Ok(Async::Ready(None));
+            }
+            let mut progressed = false;
+            let mut i = 0;
+            while i < self.in_flight.len() {
+                match self.in_flight[i].1.poll() {
+                    Ok(Async::NotReady) => i += 1,
+                    Ok(Async::Ready(response)) => {
+                        let (cell, _) = self.in_flight.swap_remove(i);
+                        self.handle(cell, response.response);
+                        progressed = true;
+                    }
+                    Err(err) => {
+                        // retain the cell so that a checkpoint resumes it
+                        let (cell, _) = self.in_flight.swap_remove(i);
+                        self.pending.push_front(cell);
+                        return Err(err);
+                    }
+                }
+            }
+            if !progressed {
+                return Ok(Async::NotReady);
+            }
+        }
+    }
+}
+
+/// Return the quadrants of a cell at the provided depth
+fn split(cell: &Cell, depth: u8) -> Vec<Cell> {
+    cell.bounds
+        .quadrants()
+        .iter()
+        .map(|bounds| {
+            Cell {
+                bounds: *bounds,
+                depth,
+            }
+        })
+        .collect()
+}
+
+/// approximate area of a bounding box in square meters
+fn area(bounds: &BoundingBox) -> f64 {
+    let height =
+        bounds.sw.distance_to(&Coords::new(bounds.ne.lat, bounds.sw.lng));
+    let center = bounds.center();
+    let width = Coords::new(center.lat, bounds.sw.lng).distance_to(
+        &Coords::new(center.lat, bounds.ne.lng),
+    );
+    height * width
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use futures::{executor, future};
+    use serde_json;
+
+    use {Client, Credentials};
+    use transport::{self, Transport};
+
+    #[derive(Default)]
+    struct State {
+        venues: Vec<(String, Coords)>,
+        /// bounds of each search, in the order they were sent
+        searches: Vec<BoundingBox>,
+        /// 1-based index of a search to fail
+        fail: Option<usize>,
+        completed: usize,
+        max_outstanding: usize,
+    }
+
+    /// Serves the first 50 venues within each searched bounding box
+    #[derive(Clone, Default)]
+    struct Stub {
+        state: Rc<RefCell<State>>,
+    }
+
+    impl Stub {
+        fn new(venues: Vec<(String, Coords)>) -> Self {
+            let stub = Stub::default();
+            stub.state.borrow_mut().venues = venues;
+            stub
+        }
+    }
+
+    fn coords(value: &str) -> Coords {
+        let mut parts = value.split(',').map(|part| part.parse().unwrap());
+        Coords::new(parts.next().unwrap(), parts.next().unwrap())
+    }
+
+    impl Transport for Stub {
+        fn send(
+            &self,
+            request: transport::Request,
+        ) -> Future<transport::Response> {
+            let param = |name: &str| {
+                request
+                    .url
+                    .query_pairs()
+                    .find(|&(ref key, _)| key == name)
+                    .map(|(_, value)| coords(&value))
+                    .unwrap()
+            };
+            let bounds = BoundingBox::new(param("sw"), param("ne"));
+            let mut state = self.state.borrow_mut();
+            state.searches.push(bounds);
+            let outstanding = state.searches.len() - state.completed;
+            state.max_outstanding = state.max_outstanding.max(outstanding);
+            let (status, body) = if state.fail == Some(state.searches.len()) {
+                (
+                    500,
+                    r#"{"meta": {"code": 500, "requestId": "1",
+                                 "errorDetail": "unavailable"},
+                        "response": {}}"#
+                        .to_owned(),
+                )
+            } else {
+                let venues = state
+                    .venues
+                    .iter()
+                    .filter(|&&(_, ref coords)| bounds.contains(coords))
+                    .take(SEARCH_LIMIT)
+                    .map(|&(ref id, ref coords)| {
+                        format!(
+                            r#"{{"id": "{}", "name": "{}", "categories": [],
+                                 "location": {{"lat": {}, "lng": {},
+                                               "country": "US"}}}}"#,
+                            id,
+                            id,
+                            coords.lat,
+                            coords.lng
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                (
+                    200,
+                    format!(
+                        r#"{{"meta": {{"code": 200, "requestId": "1"}},
+                             "response": {{"venues": [{}]}}}}"#,
+                        venues.join(",")
+                    ),
+                )
+            };
+            let state = self.state.clone();
+            // completes when first polled
+            Box::new(future::lazy(move || {
+                state.borrow_mut().completed += 1;
+                Ok(transport::Response {
+                    status,
+                    headers: Vec::new(),
+                    body: body.into_bytes(),
+                })
+            }))
+        }
+    }
+
+    /// 60 venues in a grid across a box, plus one at its center
+    fn grid(sw: Coords, size: f64) -> Vec<(String, Coords)> {
+        let mut venues = (0..60)
+            .map(|i| {
+                (
+                    format!("v{}", i),
+                    Coords::new(
+                        sw.lat + size * (0.05 + 0.9 * (i / 8) as f64 / 8.0),
+                        sw.lng + size * (0.05 + 0.9 * (i % 8) as f64 / 8.0),
+                    ),
+                )
+            })
+            .collect::<Vec<_>>();
+        venues.push((
+            "center".into(),
+            Coords::new(sw.lat + size / 2.0, sw.lng + size / 2.0),
+        ));
+        venues
+    }
+
+    fn crawl(
+        stub: &Stub,
+        checkpoint: Checkpoint,
+        options: CrawlOptions,
+    ) -> Crawl {
+        let fs = Client::with_transport(
+            "20170801",
+            Credentials::user("token"),
+            stub.clone(),
+        );
+        Crawl::resume(fs.venues(), checkpoint, options)
+    }
+
+    fn start(bounds: BoundingBox) -> Checkpoint {
+        Checkpoint {
+            pending: vec![Cell { bounds, depth: 0 }],
+            ..Default::default()
+        }
+    }
+
+    fn ids(venues: &[Venue]) -> Vec<String> {
+        venues.iter().map(|venue| venue.id.clone()).collect()
+    }
+
+    fn distinct(ids: &[String]) -> BTreeSet<String> {
+        ids.iter().cloned().collect()
+    }
+
+    #[test]
+    fn splits_saturated_cells() {
+        let sw = Coords::new(40.0, -74.0);
+        let bounds = BoundingBox::new(sw, Coords::new(40.02, -73.98));
+        let stub = Stub::new(grid(sw, 0.02));
+        let options = CrawlOptions::builder()
+            .concurrency(2usize)
+            .build()
+            .unwrap();
+        let venues = crawl(&stub, start(bounds), options)
+            .collect()
+            .wait()
+            .unwrap();
+        let ids = ids(&venues);
+        // the center venue lies within all four quadrants and the parent
+        assert_eq!(ids.len(), 61);
+        assert_eq!(distinct(&ids).len(), 61);
+        let state = stub.state.borrow();
+        assert_eq!(state.searches.len(), 5);
+        assert_eq!(&state.searches[1..], &bounds.quadrants()[..]);
+        assert_eq!(state.max_outstanding, 2)
+    }
+
+    #[test]
+    fn large_areas_do_not_use_depth() {
+        // split twice before searching, into 0.5 degree cells
+        let sw = Coords::new(0.0, 0.0);
+        let bounds = BoundingBox::new(sw, Coords::new(2.0, 2.0));
+        let stub = Stub::new(grid(sw, 0.5));
+        let options = CrawlOptions::builder().max_depth(1u8).build().unwrap();
+        let venues = crawl(&stub, start(bounds), options)
+            .collect()
+            .wait()
+            .unwrap();
+        assert_eq!(distinct(&ids(&venues)).len(), 61)
+    }
+
+    #[test]
+    fn resumes_after_errors() {
+        let sw = Coords::new(40.0, -74.0);
+        let bounds = BoundingBox::new(sw, Coords::new(40.02, -73.98));
+        let stub = Stub::new(grid(sw, 0.02));
+        stub.state.borrow_mut().fail = Some(3);
+        let options = CrawlOptions::builder()
+            .concurrency(2usize)
+            .build()
+            .unwrap();
+
+        let mut first =
+            executor::spawn(crawl(&stub, start(bounds), options.clone()));
+        let mut yielded = Vec::new();
+        loop {
+            match first.wait_stream() {
+                Some(Ok(venue)) => yielded.push(venue.id),
+                Some(Err(_)) => break,
+                None => panic!("expected an error"),
+            }
+        }
+        let checkpoint = serde_json::from_str::<Checkpoint>(
+            &serde_json::to_string(&first.get_ref().checkpoint()).unwrap(),
+        ).unwrap();
+        assert!(!checkpoint.pending.is_empty());
+
+        stub.state.borrow_mut().fail = None;
+        let rest = crawl(&stub, checkpoint, options).collect().wait().unwrap();
+        yielded.extend(ids(&rest));
+        assert_eq!(yielded.len(), 61);
+        assert_eq!(distinct(&yielded).len(), 61)
+    }
+
+    #[test]
+    fn splits_cells() {
+        let cell = Cell {
+            bounds: BoundingBox::new(
+                Coords::new(0.0, 0.0),
+                Coords::new(2.0, 2.0),
+            ),
+            depth: 0,
+        };
+        let quadrants = split(&cell, 1);
+        assert_eq!(quadrants.len(), 4);
+        assert!(quadrants.iter().all(|q| q.depth == 1));
+        assert_eq!(
+            quadrants[3].bounds,
+            BoundingBox::new(Coords::new(1.0, 1.0), Coords::new(2.0, 2.0))
+        );
+        // roughly 222km x 222km
+        assert!(area(&cell.bounds) > MAX_CELL_AREA)
+    }
+}
@@ -18,11 +18,41 @@ error_chain! {
             display("{}: '{}'", code, error.clone().meta.error_detail.unwrap())
             description("unknown error")
           }
+        InvalidHours(detail: String) {
+            display("invalid hours: {}", detail)
+            description("invalid hours")
+        }
+        UnknownTimeZone(name: String) {
+            display("unknown time zone '{}'", name)
+            description("unknown time zone")
+        }
+        RuntimeShutdown {
+            display("the background runtime has shut down")
+            description("runtime shut down")
+        }
+        Service(detail: String) {
+            display("service error: {}", detail)
+            description("service error")
+        }
     }
     foreign_links {
         Codec(SerdeError);
         Http(HttpError);
         IO(IoError);
         URI(UriError);
+        Url(::url::ParseError);
+        Tls(::native_tls::Error) #[cfg(feature = "tls")];
+        Csv(::csv::Error) #[cfg(feature = "csv")];
+        Sqlite(::rusqlite::Error) #[cfg(feature = "sqlite")];
     }
-}
\ No newline at end of file
+}
+/// Allows errors of services and layers in the tower ecosystem, which
+/// are typically boxed, to surface as `foursquare::Error`s. The boxed error
+/// is kept as the returned error's cause
+#[cfg(feature = "tower")]
+impl From<Box<::std::error::Error + Send + Sync>> for Error {
+    fn from(error: Box<::std::error::Error + Send + Sync>) -> Self {
+        let detail = error.to_string();
+        Error::with_boxed_chain(error, ErrorKind::Service(detail))
+    }
+}
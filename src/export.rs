@@ -0,0 +1,215 @@
+//! Flat file export of venues
+//!
+//! Venues may be streamed as newline-delimited JSON, one venue per line, or,
+//! with the `csv` feature enabled, as CSV rows of a flattened `Record`.
+
+use std::io::Write;
+
+#[cfg(feature = "csv")]
+use csv;
+use serde_json;
+
+use Result;
+use venue::Venue;
+
+/// A flattened representation of a venue, one column per field
+///
+/// Nested fields are flattened as follows
+///
+/// * `category_id` and `category_name` are those of the venue's primary
+///   category
+/// * `category_ids` is the ids of all of the venue's categories joined by `;`
+/// * `formatted_address` is the lines of the venue's formatted address
+///   joined by `, `
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub id: String,
+    pub name: String,
+    pub lat: f64,
+    pub lng: f64,
+    pub address: Option<String>,
+    pub formatted_address: Option<String>,
+    pub city: Option<String>,
+    pub cc: Option<String>,
+    pub category_id: Option<String>,
+    pub category_name: Option<String>,
+    pub category_ids: String,
+    pub rating: Option<f32>,
+    pub rating_signals: Option<u64>,
+    pub price_tier: Option<u16>,
+    pub url: Option<String>,
+    pub verified: Option<bool>,
+}
+
+impl<'a> From<&'a Venue> for Record {
+    fn from(venue: &'a Venue) -> Self {
+        let primary = venue.primary_category();
+        Record {
+            id: venue.id.clone(),
+            name: venue.name.clone(),
+            lat: venue.location.lat,
+            lng: venue.location.lng,
+            address: venue.location.address.clone(),
+            formatted_address: venue.location.formatted_address.as_ref().map(
+                |lines| lines.join(", "),
+            ),
+            city: venue.location.city.clone(),
+            cc: venue.location.cc.clone(),
+            category_id: primary.map(|c| c.id.clone()),
+            category_name: primary.map(|c| c.name.clone()),
+            category_ids: venue
+                .categories
+                .iter()
+                .map(|c| c.id.as_str())
+                .collect::<Vec<_>>()
+                .join(";"),
+            rating: venue.rating,
+            rating_signals: venue.rating_signals,
+            price_tier: venue.price.as_ref().map(|p| p.tier),
+            url: venue.url.clone(),
+            verified: venue.verified,
+        }
+    }
+}
+
+/// Writes venues as CSV `Record` rows, preceded by a header row
+#[cfg(feature = "csv")]
+pub struct CsvWriter<W>
+where
+    W: Write,
+{
+    inner: csv::Writer<W>,
+}
+
+#[cfg(feature = "csv")]
+impl<W> CsvWriter<W>
+where
+    W: Write,
+{
+    /// Return a new writer
+    pub fn new(writer: W) -> Self {
+        Self { inner: csv::Writer::from_writer(writer) }
+    }
+
+    /// Write a single venue
+    pub fn write(&mut self, venue: &Venue) -> Result<()> {
+        Ok(self.inner.serialize(Record::from(venue))?)
+    }
+
+    /// Write each of the provided venues
+    pub fn write_all<'a, I>(&mut self, venues: I) -> Result<()>
+    where
+        I: IntoIterator<Item = &'a Venue>,
+    {
+        for venue in venues {
+            self.write(venue)?
+        }
+        Ok(())
+    }
+
+    /// Flush buffered rows to the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.inner.flush()?)
+    }
+}
+
+/// Writes venues as newline-delimited JSON, one venue per line
+pub struct NdjsonWriter<W>
+where
+    W: Write,
+{
+    inner: W,
+}
+
+impl<W> NdjsonWriter<W>
+where
+    W: Write,
+{
+    /// Return a new writer
+    pub fn new(writer: W) -> Self {
+        Self { inner: writer }
+    }
+
+    /// Write a single venue
+    pub fn write(&mut self, venue: &Venue) -> Result<()> {
+        serde_json::to_writer(&mut self.inner, venue)?;
+        Ok(self.inner.write_all(b"\n")?)
+    }
+
+    /// Write each of the provided venues
+    pub fn write_all<'a, I>(&mut self, venues: I) -> Result<()>
+    where
+        I: IntoIterator<Item = &'a Venue>,
+    {
+        for venue in venues {
+            self.write(venue)?
+        }
+        Ok(())
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        Ok(self.inner.flush()?)
+    }
+
+    /// Return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use venue::SearchResponse;
+
+    fn venues() -> Vec<Venue> {
+        serde_json::from_str::<SearchResponse>(
+            r#"{"venues": [{
+              "id": "1", "name": "Cafe, Bakery",
+              "location": {
+                "address": "1 Main St", "lat": 37.5, "lng": 126.9,
+                "cc": "KR", "city": "Seoul", "country": "South Korea",
+                "formattedAddress": ["1 Main St", "Seoul"]
+              },
+              "categories": [
+                {"id": "cafe", "name": "Café", "pluralName": "Cafés",
+                 "shortName": "Café", "icon": {"prefix": "p", "suffix": ".png"}},
+                {"id": "bakery", "name": "Bakery", "pluralName": "Bakeries",
+                 "shortName": "Bakery", "primary": true,
+                 "icon": {"prefix": "p", "suffix": ".png"}}
+              ],
+              "rating": 8.5
+            }]}"#,
+        ).unwrap()
+            .venues
+    }
+
+    #[cfg(feature = "csv")]
+    #[test]
+    fn writes_csv() {
+        let mut writer = CsvWriter::new(Vec::new());
+        writer.write_all(&venues()).unwrap();
+        let bytes = writer.inner.into_inner().unwrap();
+        assert_eq!(
+            String::from_utf8(bytes).unwrap(),
+            "id,name,lat,lng,address,formatted_address,city,cc,category_id,\
+             category_name,category_ids,rating,rating_signals,price_tier,url,\
+             verified\n\
+             1,\"Cafe, Bakery\",37.5,126.9,1 Main St,\"1 Main St, Seoul\",\
+             Seoul,KR,bakery,Bakery,cafe;bakery,8.5,,,,\n"
+        )
+    }
+
+    #[test]
+    fn writes_ndjson() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        writer.write_all(&venues()).unwrap();
+        writer.write_all(&venues()).unwrap();
+        let lines = String::from_utf8(writer.into_inner()).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        for line in lines.lines() {
+            assert_eq!(serde_json::from_str::<Venue>(line).unwrap().id, "1")
+        }
+    }
+}
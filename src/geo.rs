@@ -0,0 +1,329 @@
+//! Geospatial utilities
+//!
+//! Distances are computed with the
+//! [haversine formula](https://en.wikipedia.org/wiki/Haversine_formula)
+//! and are measured in meters, the unit the api uses for `radius` and
+//! `Location.distance`.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+use venue::{Coords, Location, SearchResponse, Venue};
+
+/// Mean radius of the earth in meters
+pub const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// A type with a latitude and longitude
+pub trait Position {
+    fn coords(&self) -> Coords;
+
+    /// Return the great-circle distance to another position in meters
+    fn distance_to<P>(&self, other: &P) -> f64
+    where
+        P: Position,
+        Self: Sized,
+    {
+        distance(&self.coords(), &other.coords())
+    }
+
+    /// Return the initial bearing to another position in degrees clockwise
+    /// from north, in the range [0, 360)
+    fn bearing_to<P>(&self, other: &P) -> f64
+    where
+        P: Position,
+        Self: Sized,
+    {
+        bearing(&self.coords(), &other.coords())
+    }
+}
+
+impl Position for Coords {
+    fn coords(&self) -> Coords {
+        *self
+    }
+}
+
+impl Position for Location {
+    fn coords(&self) -> Coords {
+        Coords {
+            lat: self.lat,
+            lng: self.lng,
+        }
+    }
+}
+
+impl Position for Venue {
+    fn coords(&self) -> Coords {
+        self.location.coords()
+    }
+}
+
+impl Coords {
+    /// Return new coordinates
+    pub fn new(lat: f64, lng: f64) -> Self {
+        Coords { lat, lng }
+    }
+}
+
+/// Formats coordinates as `lat,lng`, the format of api `ll`, `sw` and `ne`
+/// options
+impl fmt::Display for Coords {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.lat, self.lng)
+    }
+}
+
+/// Return the great-circle distance between two coordinates in meters
+pub fn distance(a: &Coords, b: &Coords) -> f64 {
+    let (lat1, lat2) = (a.lat.to_radians(), b.lat.to_radians());
+    let dlat = lat2 - lat1;
+    let dlng = (b.lng - a.lng).to_radians();
+    let h = (dlat / 2.0).sin().powi(2) +
+        lat1.cos() * lat2.cos() * (dlng / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * h.sqrt().min(1.0).asin()
+}
+
+/// Return the initial bearing from one coordinate to another in degrees
+/// clockwise from north, in the range [0, 360)
+pub fn bearing(from: &Coords, to: &Coords) -> f64 {
+    let (lat1, lat2) = (from.lat.to_radians(), to.lat.to_radians());
+    let dlng = (to.lng - from.lng).to_radians();
+    let y = dlng.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlng.cos();
+    (y.atan2(x).to_degrees() + 360.0) % 360.0
+}
+
+/// Wrap a longitude into the range [-180, 180]
+fn normalize_lng(lng: f64) -> f64 {
+    if -180.0 <= lng && lng <= 180.0 {
+        lng
+    } else {
+        ((lng + 180.0) % 360.0 + 360.0) % 360.0 - 180.0
+    }
+}
+
+/// A rectangular area defined by its south-west and north-east corners
+///
+/// A box whose west longitude is greater than its east longitude crosses
+/// the antimeridian
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub sw: Coords,
+    pub ne: Coords,
+}
+
+impl BoundingBox {
+    /// Return a new bounding box
+    pub fn new(sw: Coords, ne: Coords) -> Self {
+        BoundingBox { sw, ne }
+    }
+
+    /// Return the smallest bounding box containing a circle of `radius`
+    /// meters around a center point
+    pub fn around<P>(center: &P, radius: f64) -> Self
+    where
+        P: Position,
+    {
+        let center = center.coords();
+        let dlat = (radius / EARTH_RADIUS).to_degrees();
+        let dlng = if center.lat.abs() + dlat >= 90.0 {
+            // the circle includes a pole, span all longitudes
+            180.0
+        } else {
+            (radius / (EARTH_RADIUS * center.lat.to_radians().cos()))
+                .to_degrees()
+                .min(180.0)
+        };
+        let (west, east) = if dlng >= 180.0 {
+            (-180.0, 180.0)
+        } else {
+            (normalize_lng(center.lng - dlng), normalize_lng(center.lng + dlng))
+        };
+        BoundingBox {
+            sw: Coords::new((center.lat - dlat).max(-90.0), west),
+            ne: Coords::new((center.lat + dlat).min(90.0), east),
+        }
+    }
+
+    /// Return true if the bounding box crosses the antimeridian
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.sw.lng > self.ne.lng
+    }
+
+    /// Return the center of the bounding box
+    pub fn center(&self) -> Coords {
+        let east = if self.crosses_antimeridian() {
+            self.ne.lng + 360.0
+        } else {
+            self.ne.lng
+        };
+        Coords::new(
+            (self.sw.lat + self.ne.lat) / 2.0,
+            normalize_lng((self.sw.lng + east) / 2.0),
+        )
+    }
+
+    /// Return true if the bounding box contains a position
+    pub fn contains<P>(&self, position: &P) -> bool
+    where
+        P: Position,
+    {
+        let Coords { lat, lng } = position.coords();
+        let lng = normalize_lng(lng);
+        let within_lng = if self.crosses_antimeridian() {
+            self.sw.lng <= lng || lng <= self.ne.lng
+        } else {
+            self.sw.lng <= lng && lng <= self.ne.lng
+        };
+        self.sw.lat <= lat && lat <= self.ne.lat && within_lng
+    }
+
+    /// Return the four quadrants of this bounding box, ordered south-west,
+    /// south-east, north-west, north-east
+    pub fn quadrants(&self) -> [BoundingBox; 4] {
+        let center = self.center();
+        [
+            BoundingBox::new(self.sw, center),
+            BoundingBox::new(
+                Coords::new(self.sw.lat, center.lng),
+                Coords::new(center.lat, self.ne.lng),
+            ),
+            BoundingBox::new(
+                Coords::new(center.lat, self.sw.lng),
+                Coords::new(self.ne.lat, center.lng),
+            ),
+            BoundingBox::new(center, self.ne),
+        ]
+    }
+}
+
+/// Return true if a position falls within a polygon of vertices
+///
+/// Polygons are treated as planar, which is a reasonable approximation
+/// for areas the size of a city
+pub fn contains<P>(polygon: &[Coords], position: &P) -> bool
+where
+    P: Position,
+{
+    let Coords { lat, lng } = position.coords();
+    let mut inside = false;
+    let mut j = polygon.len().wrapping_sub(1);
+    for (i, a) in polygon.iter().enumerate() {
+        let b = &polygon[j];
+        if (a.lat > lat) != (b.lat > lat) &&
+            lng < (b.lng - a.lng) * (lat - a.lat) / (b.lat - a.lat) + a.lng
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+impl Location {
+    /// Fill in `distance` from the provided position when the api
+    /// did not include one
+    pub fn fill_distance<P>(&mut self, from: &P)
+    where
+        P: Position,
+    {
+        if self.distance.is_none() {
+            let meters = self.distance_to(&from.coords());
+            self.distance = Some(meters.round() as u32)
+        }
+    }
+}
+
+impl SearchResponse {
+    /// Sort venues by distance from a position, nearest first
+    pub fn sort_by_distance<P>(&mut self, from: &P)
+    where
+        P: Position,
+    {
+        let from = from.coords();
+        self.venues.sort_by(|a, b| {
+            a.distance_to(&from)
+                .partial_cmp(&b.distance_to(&from))
+                .unwrap_or(Ordering::Equal)
+        })
+    }
+
+    /// Fill in each venue's `location.distance` from a position when the
+    /// api did not include one
+    pub fn fill_distances<P>(&mut self, from: &P)
+    where
+        P: Position,
+    {
+        for venue in &mut self.venues {
+            venue.location.fill_distance(from)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_and_bearing() {
+        let seoul = Coords::new(37.5665, 126.9780);
+        let busan = Coords::new(35.1796, 129.0756);
+        let meters = seoul.distance_to(&busan);
+        assert!((meters - 325_000.0).abs() < 1_000.0, "{}", meters);
+        let degrees = seoul.bearing_to(&busan);
+        assert!(degrees > 130.0 && degrees < 150.0, "{}", degrees);
+        assert_eq!(
+            Coords::new(0.0, 0.0).bearing_to(&Coords::new(1.0, 0.0)),
+            0.0
+        )
+    }
+
+    #[test]
+    fn bounding_boxes() {
+        let center = Coords::new(40.7686834, -73.9539324);
+        let bounds = BoundingBox::around(&center, 1_000.0);
+        assert!(bounds.contains(&center));
+        assert!(
+            (center.distance_to(&Coords::new(bounds.ne.lat, center.lng)) -
+                 1_000.0)
+                .abs() < 1.0
+        );
+        let quadrants = bounds.quadrants();
+        assert!(quadrants[0].contains(&bounds.sw));
+        assert!(quadrants[3].contains(&bounds.ne))
+    }
+
+    #[test]
+    fn antimeridian_bounding_boxes() {
+        let center = Coords::new(-17.7, 179.99);
+        let bounds = BoundingBox::around(&center, 10_000.0);
+        assert!(bounds.crosses_antimeridian());
+        assert!(bounds.ne.lng >= -180.0 && bounds.ne.lng < -179.9);
+        assert!(bounds.sw.lng <= 180.0 && bounds.sw.lng > 179.8);
+        assert!(bounds.contains(&center));
+        assert!(bounds.contains(&Coords::new(-17.7, -179.95)));
+        assert!(!bounds.contains(&Coords::new(-17.7, 0.0)));
+        assert!(!bounds.contains(&Coords::new(-17.7, 179.0)));
+        assert!((bounds.center().lng - 179.99).abs() < 1e-9);
+        let quadrants = bounds.quadrants();
+        assert!(quadrants[0].contains(&bounds.sw));
+        assert!(quadrants[3].contains(&bounds.ne));
+        assert!(quadrants.iter().any(|q| q.contains(&center)));
+
+        let polar = BoundingBox::around(&Coords::new(89.9, 10.0), 100_000.0);
+        assert_eq!((polar.sw.lng, polar.ne.lng), (-180.0, 180.0))
+    }
+
+    #[test]
+    fn point_in_polygon() {
+        let square = [
+            Coords::new(0.0, 0.0),
+            Coords::new(0.0, 1.0),
+            Coords::new(1.0, 1.0),
+            Coords::new(1.0, 0.0),
+        ];
+        assert!(contains(&square, &Coords::new(0.5, 0.5)));
+        assert!(!contains(&square, &Coords::new(1.5, 0.5)));
+        assert!(!contains(&[], &Coords::new(0.5, 0.5)))
+    }
+}
@@ -0,0 +1,256 @@
+//! GeoJSON export of venues
+//!
+//! Venue results of any operation may be exported as a
+//! [GeoJSON](https://tools.ietf.org/html/rfc7946) `FeatureCollection` of
+//! `Point` features, suitable for rendering on a map
+//!
+//! ```no_run
+//! extern crate foursquare;
+//!
+//! use foursquare::venue::SearchResponse;
+//! use foursquare::geojson::{FeatureCollection, Property, ToGeoJson};
+//!
+//! fn names(response: &SearchResponse) -> FeatureCollection {
+//!   response.to_geojson_with(&[Property::Id, Property::Name])
+//! }
+//! # fn main() {}
+//! ```
+
+use serde_json::{Map, Value};
+
+use venue::{Venue, VenueResults};
+
+/// A venue property to include in a feature's properties
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Property {
+    /// `id`, the venue id
+    Id,
+    /// `name`, the venue name
+    Name,
+    /// `category`, the name of the venue's primary category
+    Category,
+    /// `category_id`, the id of the venue's primary category
+    CategoryId,
+    /// `rating`, the venue's rating from 0 through 10
+    Rating,
+    /// `price_tier`, the venue's price tier from 1 through 4
+    PriceTier,
+    /// `address`, the venue's formatted address joined by ", "
+    Address,
+    /// `url`, the venue's website
+    Url,
+    /// `verified`, whether the venue has been claimed by its owner
+    Verified,
+    /// `distance`, the venue's distance in meters from the search location
+    Distance,
+}
+
+/// The properties included by `ToGeoJson::to_geojson`
+pub const DEFAULT_PROPERTIES: &[Property] = &[
+    Property::Id,
+    Property::Name,
+    Property::Category,
+    Property::Rating,
+    Property::PriceTier,
+    Property::Address,
+];
+
+impl Property {
+    fn key(&self) -> &'static str {
+        match *self {
+            Property::Id => "id",
+            Property::Name => "name",
+            Property::Category => "category",
+            Property::CategoryId => "category_id",
+            Property::Rating => "rating",
+            Property::PriceTier => "price_tier",
+            Property::Address => "address",
+            Property::Url => "url",
+            Property::Verified => "verified",
+            Property::Distance => "distance",
+        }
+    }
+
+    fn value(&self, venue: &Venue) -> Value {
+        match *self {
+            Property::Id => venue.id.clone().into(),
+            Property::Name => venue.name.clone().into(),
+            Property::Category => {
+                venue
+                    .primary_category()
+                    .map(|c| c.name.clone().into())
+                    .unwrap_or(Value::Null)
+            }
+            Property::CategoryId => {
+                venue
+                    .primary_category()
+                    .map(|c| c.id.clone().into())
+                    .unwrap_or(Value::Null)
+            }
+            // ratings have one decimal place, rounding avoids f32 noise such
+            // as 8.100000381 when widened to a json f64
+            Property::Rating => {
+                venue
+                    .rating
+                    .map(|rating| {
+                        Value::from((f64::from(rating) * 10.0).round() / 10.0)
+                    })
+                    .unwrap_or(Value::Null)
+            }
+            Property::PriceTier => {
+                venue
+                    .price
+                    .as_ref()
+                    .map(|p| p.tier.into())
+                    .unwrap_or(Value::Null)
+            }
+            Property::Address => {
+                venue
+                    .location
+                    .formatted_address
+                    .as_ref()
+                    .map(|lines| lines.join(", ").into())
+                    .unwrap_or(Value::Null)
+            }
+            Property::Url => venue.url.clone().map(Value::from).unwrap_or(
+                Value::Null,
+            ),
+            Property::Verified => venue.verified.map(Value::from).unwrap_or(
+                Value::Null,
+            ),
+            Property::Distance => {
+                venue.location.distance.map(Value::from).unwrap_or(
+                    Value::Null,
+                )
+            }
+        }
+    }
+}
+
+/// A GeoJSON Point geometry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Point {
+    #[serde(rename = "type")]
+    pub geometry_type: String,
+    /// longitude, latitude
+    pub coordinates: [f64; 2],
+}
+
+/// A GeoJSON Feature
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feature {
+    #[serde(rename = "type")]
+    pub feature_type: String,
+    pub id: String,
+    pub geometry: Point,
+    pub properties: Map<String, Value>,
+}
+
+/// A GeoJSON FeatureCollection
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureCollection {
+    #[serde(rename = "type")]
+    pub collection_type: String,
+    pub features: Vec<Feature>,
+}
+
+impl Feature {
+    /// Return a new Point feature for a venue with the provided properties
+    pub fn new(venue: &Venue, properties: &[Property]) -> Self {
+        Feature {
+            feature_type: "Feature".into(),
+            id: venue.id.clone(),
+            geometry: Point {
+                geometry_type: "Point".into(),
+                coordinates: [venue.location.lng, venue.location.lat],
+            },
+            properties: properties
+                .iter()
+                .map(|p| (p.key().to_owned(), p.value(venue)))
+                .collect(),
+        }
+    }
+}
+
+fn collection<'a, I>(venues: I, properties: &[Property]) -> FeatureCollection
+where
+    I: IntoIterator<Item = &'a Venue>,
+{
+    FeatureCollection {
+        collection_type: "FeatureCollection".into(),
+        features: venues
+            .into_iter()
+            .map(|venue| Feature::new(venue, properties))
+            .collect(),
+    }
+}
+
+/// Types which may be exported as GeoJSON, implemented for all
+/// `VenueResults`
+pub trait ToGeoJson {
+    /// Return a FeatureCollection with the `DEFAULT_PROPERTIES`
+    fn to_geojson(&self) -> FeatureCollection {
+        self.to_geojson_with(DEFAULT_PROPERTIES)
+    }
+
+    /// Return a FeatureCollection with the provided properties
+    fn to_geojson_with(&self, properties: &[Property]) -> FeatureCollection;
+}
+
+impl<T> ToGeoJson for T
+where
+    T: VenueResults + ?Sized,
+{
+    fn to_geojson_with(&self, properties: &[Property]) -> FeatureCollection {
+        collection(self.venues(), properties)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+    use venue::SearchResponse;
+
+    #[test]
+    fn exports_points() {
+        let response = serde_json::from_str::<SearchResponse>(
+            r#"{"venues": [{
+              "id": "1", "name": "Cafe", "rating": 8.1,
+              "location": {
+                "lat": 37.5665, "lng": 126.978, "country": "South Korea",
+                "formattedAddress": ["Seoul", "South Korea"]
+              },
+              "categories": [
+                {"id": "coffee", "name": "Coffee Shop",
+                 "pluralName": "Coffee Shops", "shortName": "Coffee Shop",
+                 "icon": {"prefix": "p", "suffix": ".png"}, "primary": true}
+              ]
+            }]}"#,
+        ).unwrap();
+        assert_eq!(
+            serde_json::to_value(response.to_geojson()).unwrap(),
+            serde_json::from_str::<Value>(
+                r#"{
+                "type": "FeatureCollection",
+                "features": [{
+                    "type": "Feature",
+                    "id": "1",
+                    "geometry": {
+                        "type": "Point",
+                        "coordinates": [126.978, 37.5665]
+                    },
+                    "properties": {
+                        "id": "1",
+                        "name": "Cafe",
+                        "category": "Coffee Shop",
+                        "rating": 8.1,
+                        "price_tier": null,
+                        "address": "Seoul, South Korea"
+                    }
+                }]
+            }"#,
+            ).unwrap()
+        )
+    }
+}
@@ -0,0 +1,288 @@
+//! Lenient deserialization of venue lists
+//!
+//! By default a single malformed venue, e.g. one missing a required field
+//! or carrying an unknown `Feature`, fails its whole response with
+//! `ErrorKind::Codec`. A lenient `Client`, see `Client::lenient`, instead
+//! skips malformed items within `venues`, `minivenues`, `groups[].items` and
+//! `group.results` lists, recording a `Warning` for each on the
+//! `Response`.
+//!
+//! ```
+//! extern crate foursquare;
+//! extern crate futures;
+//!
+//! use futures::Future;
+//! use foursquare::{Client, Credentials};
+//! use foursquare::transport::MemoryTransport;
+//! use foursquare::venue::SearchOptions;
+//!
+//! fn main() {
+//!   let transport = MemoryTransport::new();
+//!   transport.respond(
+//!     "/v2/venues/search",
+//!     200,
+//!     r#"{"meta": {"code": 200, "requestId": "1"},
+//!         "response": {"venues": [{"id": "1", "name": "nowhere"}]}}"#
+//!   );
+//!   let fs = Client::with_transport(
+//!     "YYYYMMDD",
+//!     Credentials::client("client_id", "client_secret"),
+//!     transport
+//!   ).lenient(true);
+//!   let response = fs.venues()
+//!     .search(&SearchOptions::builder().near("Brooklyn, NY").build().unwrap())
+//!     .wait()
+//!     .unwrap();
+//!   assert!(response.response.venues.is_empty());
+//!   for warning in response.warnings {
+//!     println!("skipped {}", warning);
+//!   }
+//! }
+//! ```
+
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::{self, Value};
+
+use venue::{CategoriesResponse, ExploreResponse, Recommendation,
+            RecommendationsResponse, SearchResponse, SuggestResponse,
+            TipsResponse, Venue, VenueHoursResponse, VenueItem, VenueResponse};
+
+/// A list item skipped because it could not be deserialized
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Warning {
+    /// Location of the item within the response, e.g. `groups[0].items[3]`
+    pub path: String,
+    /// The item's venue id, if it has one
+    pub id: Option<String>,
+    /// Why the item could not be deserialized
+    pub error: String,
+    /// The item's raw json
+    pub value: Value,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.id {
+            Some(ref id) => write!(f, "{} ({}): {}", self.path, id, self.error),
+            _ => write!(f, "{}: {}", self.path, self.error),
+        }
+    }
+}
+
+/// Response types whose lists may be leniently deserialized
+pub trait Lenient {
+    /// Remove list items which can't be deserialized from a raw json
+    /// response, recording a warning for each. Types without lists keep the
+    /// default, which removes nothing
+    fn skip_malformed(_response: &mut Value, _warnings: &mut Vec<Warning>) {}
+}
+
+/// Remove items of the json array at `path`, if present, which can't be
+/// deserialized as `T`
+pub fn skip_malformed<T>(
+    list: Option<&mut Value>,
+    path: &str,
+    warnings: &mut Vec<Warning>,
+) where
+    T: DeserializeOwned,
+{
+    let items = match list {
+        Some(&mut Value::Array(ref mut items)) => items,
+        _ => return,
+    };
+    let mut index = 0;
+    items.retain(|item| {
+        let kept = match serde_json::from_value::<T>(item.clone()) {
+            Ok(_) => true,
+            Err(error) => {
+                let warning = Warning {
+                    path: format!("{}[{}]", path, index),
+                    id: id(item),
+                    error: error.to_string(),
+                    value: item.clone(),
+                };
+                warn!("skipping malformed item {}", warning);
+                warnings.push(warning);
+                false
+            }
+        };
+        index += 1;
+        kept
+    })
+}
+
+impl Lenient for CategoriesResponse {}
+
+impl Lenient for TipsResponse {}
+
+impl Lenient for VenueHoursResponse {}
+
+impl Lenient for VenueResponse {}
+
+impl Lenient for SearchResponse {
+    fn skip_malformed(response: &mut Value, warnings: &mut Vec<Warning>) {
+        skip_malformed::<Venue>(response.get_mut("venues"), "venues", warnings)
+    }
+}
+
+impl Lenient for SuggestResponse {
+    fn skip_malformed(response: &mut Value, warnings: &mut Vec<Warning>) {
+        skip_malformed::<Venue>(
+            response.get_mut("minivenues"),
+            "minivenues",
+            warnings,
+        )
+    }
+}
+
+impl Lenient for ExploreResponse {
+    fn skip_malformed(response: &mut Value, warnings: &mut Vec<Warning>) {
+        if let Some(&mut Value::Array(ref mut groups)) =
+            response.get_mut("groups")
+        {
+            for (index, group) in groups.iter_mut().enumerate() {
+                skip_malformed::<VenueItem>(
+                    group.get_mut("items"),
+                    &format!("groups[{}].items", index),
+                    warnings,
+                )
+            }
+        }
+    }
+}
+
+impl Lenient for RecommendationsResponse {
+    fn skip_malformed(response: &mut Value, warnings: &mut Vec<Warning>) {
+        skip_malformed::<Recommendation>(
+            response.pointer_mut("/group/results"),
+            "group.results",
+            warnings,
+        )
+    }
+}
+
+/// The venue id of a venue, or of a venue nested within an item
+fn id(item: &Value) -> Option<String> {
+    item.get("id")
+        .or_else(|| item.get("venue").and_then(|venue| venue.get("id")))
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Body;
+
+    const SOMEWHERE: &str = r#"{"id": "1", "name": "somewhere",
+                                "location": {"lat": 40.72, "lng": -73.95,
+                                             "country": "United States"},
+                                "categories": []}"#;
+
+    /// lacks the required location country
+    const NOWHERE: &str = r#"{"id": "2", "name": "nowhere",
+                              "location": {"lat": 40.72, "lng": -73.95},
+                              "categories": []}"#;
+
+    fn body(response: &str) -> Body {
+        Body::from(
+            format!(
+                r#"{{"meta": {{"code": 200, "requestId": "1"}},
+                    "response": {}}}"#,
+                response
+            ).into_bytes(),
+        )
+    }
+
+    #[test]
+    fn skips_malformed_items() {
+        let body = Body::from(
+            br#"{"meta": {"code": 200, "requestId": "1"},
+                 "response": {"groups": [{
+                   "type": "Recommended Places", "name": "recommended",
+                   "items": [
+                     {"referralId": "e-0-1-0",
+                      "venue": {"id": "1", "name": "somewhere",
+                                "location": {"lat": 40.72, "lng": -73.95,
+                                             "country": "United States"},
+                                "categories": []}},
+                     {"referralId": "e-0-2-0",
+                      "venue": {"id": "2", "name": "nowhere",
+                                "location": {"lat": 40.72, "lng": -73.95},
+                                "categories": []}}
+                   ]}],
+                   "headerLocation": "SoHo",
+                   "headerFullLocation": "SoHo, New York",
+                   "headerLocationGranularity": "neighborhood",
+                   "totalResults": 2}}"#
+                .to_vec(),
+        );
+        assert!(body.parse::<ExploreResponse>().is_err());
+        let response = body.parse_lenient::<ExploreResponse>().unwrap();
+        let items = &response.response.groups[0].items;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].venue.id, "1");
+        assert_eq!(response.warnings.len(), 1);
+        let warning = &response.warnings[0];
+        assert_eq!(warning.path, "groups[0].items[1]");
+        assert_eq!(warning.id, Some("2".into()));
+        assert!(warning.error.contains("country"));
+        assert_eq!(warning.value["referralId"], "e-0-2-0")
+    }
+
+    #[test]
+    fn skips_malformed_venues() {
+        let body = body(&format!(
+            r#"{{"venues": [{}, {}]}}"#,
+            NOWHERE,
+            SOMEWHERE
+        ));
+        assert!(body.parse::<SearchResponse>().is_err());
+        let response = body.parse_lenient::<SearchResponse>().unwrap();
+        assert_eq!(response.response.venues.len(), 1);
+        assert_eq!(response.response.venues[0].id, "1");
+        assert_eq!(response.warnings.len(), 1);
+        assert_eq!(response.warnings[0].path, "venues[0]");
+        assert_eq!(response.warnings[0].id, Some("2".into()))
+    }
+
+    #[test]
+    fn skips_malformed_minivenues() {
+        let body = body(&format!(
+            r#"{{"minivenues": [{}, {}]}}"#,
+            SOMEWHERE,
+            NOWHERE
+        ));
+        assert!(body.parse::<SuggestResponse>().is_err());
+        let response = body.parse_lenient::<SuggestResponse>().unwrap();
+        assert_eq!(response.response.minivenues.len(), 1);
+        assert_eq!(response.response.minivenues[0].id, "1");
+        assert_eq!(response.warnings.len(), 1);
+        assert_eq!(response.warnings[0].path, "minivenues[1]");
+        assert_eq!(response.warnings[0].id, Some("2".into()))
+    }
+
+    #[test]
+    fn skips_malformed_recommendations() {
+        let body = body(&format!(
+            r#"{{"group": {{"totalResults": 2, "results": [
+                   {{"displayType": "venue", "venue": {}}},
+                   {{"displayType": "venue", "venue": {}}}
+                ]}}}}"#,
+            NOWHERE,
+            SOMEWHERE
+        ));
+        assert!(body.parse::<RecommendationsResponse>().is_err());
+        let response = body.parse_lenient::<RecommendationsResponse>()
+            .unwrap();
+        let results = &response.response.group.results;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].venue.id, "1");
+        assert_eq!(response.warnings.len(), 1);
+        assert_eq!(response.warnings[0].path, "group.results[0]");
+        assert_eq!(response.warnings[0].id, Some("2".into()));
+        assert_eq!(response.warnings[0].value["displayType"], "venue")
+    }
+}
@@ -43,14 +43,23 @@
 //!
 #![allow(missing_docs)] // todo: make this a deny eventually
 
+extern crate base64;
+extern crate chrono;
+extern crate chrono_tz;
+#[cfg(feature = "csv")]
+extern crate csv;
 #[macro_use]
 extern crate derive_builder;
+#[cfg(feature = "compression")]
+extern crate flate2;
 extern crate futures;
 #[macro_use]
 extern crate error_chain;
 #[macro_use]
 extern crate log;
 extern crate hyper;
+#[cfg(feature = "sqlite")]
+extern crate rusqlite;
 #[macro_use]
 extern crate serde_derive;
 extern crate serde;
@@ -58,26 +67,78 @@ extern crate serde_json;
 extern crate serde_urlencoded;
 extern crate url;
 extern crate tokio_core;
+extern crate tokio_io;
+extern crate tokio_service;
+#[cfg(feature = "tower")]
+extern crate tower_layer;
+#[cfg(feature = "tower")]
+extern crate tower_service;
 #[cfg(feature = "tls")]
 extern crate hyper_tls;
+#[cfg(feature = "tls")]
+extern crate native_tls;
+#[cfg(feature = "rustls")]
+extern crate hyper_rustls;
 
 #[cfg(test)]
 #[macro_use]
 extern crate pretty_assertions;
 
-use futures::{Future as StdFuture, IntoFuture, Stream as StdStream};
-use hyper::{Client as HyperClient, Method};
-use hyper::client::{Connect, HttpConnector, Request};
+use std::fmt;
+use std::rc::Rc;
+
+use futures::Future as StdFuture;
+use hyper::{Client as HyperClient, StatusCode};
+use hyper::client::Connect;
+#[cfg(feature = "tls")]
+use hyper::client::HttpConnector;
 #[cfg(feature = "tls")]
 use hyper_tls::HttpsConnector;
+#[cfg(feature = "tls")]
+use native_tls::TlsConnector;
+use log::LogLevel;
+use serde::Deserialize;
 use serde::de::DeserializeOwned;
+#[cfg(any(feature = "tls", feature = "rustls"))]
 use tokio_core::reactor::Handle;
+#[cfg(any(feature = "tls", feature = "rustls"))]
+use connection::ConnectionOptions;
+#[cfg(feature = "tls")]
+use connection::Nodelay;
+use lenient::{Lenient, Warning};
+#[cfg(feature = "tls")]
+use proxy::{Proxy, ProxyConnector};
 use url::Url;
 
 pub mod venue;
 pub use venue::Venues;
 pub mod error;
 pub use error::{Error, ErrorKind, Result};
+#[cfg(all(feature = "std-future", any(feature = "tls", feature = "rustls")))]
+pub mod asynchronous;
+#[cfg(any(feature = "tls", feature = "rustls"))]
+pub mod blocking;
+#[cfg(any(feature = "tls", feature = "rustls"))]
+mod runtime;
+#[cfg(feature = "compression")]
+mod compression;
+#[cfg(any(feature = "tls", feature = "rustls"))]
+pub mod connection;
+pub mod crawl;
+pub mod export;
+pub mod geo;
+pub mod lenient;
+pub mod proxy;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+#[cfg(feature = "tower")]
+pub mod service;
+pub mod transport;
+#[cfg(all(test, any(feature = "tls", feature = "rustls")))]
+mod testing;
+pub use transport::{HyperTransport, MemoryTransport, Transport};
 
 const DEFAULT_HOST: &str = "https://api.foursquare.com";
 
@@ -122,19 +183,28 @@ impl Credentials {
 }
 
 /// Entry point interface for interacting with Foursquare API
-#[derive(Clone, Debug)]
-pub struct Client<C>
-where
-    C: Clone + Connect,
-{
+#[derive(Clone)]
+pub struct Client {
     host: String,
     version: String,
-    http: HyperClient<C>,
+    transport: Rc<Transport>,
     credentials: Credentials,
+    lenient: bool,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Client")
+            .field("host", &self.host)
+            .field("version", &self.version)
+            .field("credentials", &self.credentials)
+            .field("lenient", &self.lenient)
+            .finish()
+    }
 }
 
 #[cfg(feature = "tls")]
-impl Client<HttpsConnector<HttpConnector>> {
+impl Client {
     /// returns a new client
     ///
     /// version should be in `YYYYMMDD` format
@@ -142,57 +212,261 @@ impl Client<HttpsConnector<HttpConnector>> {
     where
         V: Into<String>,
     {
-        let connector = HttpsConnector::new(4, handle).unwrap();
-        let http = HyperClient::configure()
+        Self::with_options(
+            version,
+            credentials,
+            handle,
+            &ConnectionOptions::default(),
+        ).unwrap()
+    }
+
+    /// returns a new client with custom keep-alive and connector settings.
+    /// See `connection` for what can be tuned
+    ///
+    /// version should be in `YYYYMMDD` format
+    pub fn with_options<V>(
+        version: V,
+        credentials: Credentials,
+        handle: &Handle,
+        options: &ConnectionOptions,
+    ) -> Result<Self>
+    where
+        V: Into<String>,
+    {
+        let mut http = HttpConnector::new(options.dns_threads, handle);
+        http.enforce_http(false);
+        http.set_keepalive(options.tcp_keepalive);
+        let tcp = Nodelay::new(http, options.nodelay);
+        let tls = TlsConnector::builder()?.build()?;
+        let config = options.configure(HyperClient::configure());
+        Ok(match options.proxy {
+            Some(ref proxy) => {
+                let connector = HttpsConnector::from(
+                    (ProxyConnector::new(tcp, proxy.clone()), tls),
+                );
+                Self::custom(
+                    version,
+                    credentials,
+                    config.connector(connector).build(handle),
+                )
+            }
+            _ => {
+                let connector = HttpsConnector::from((tcp, tls));
+                Self::custom(
+                    version,
+                    credentials,
+                    config.connector(connector).build(handle),
+                )
+            }
+        })
+    }
+
+    /// returns a new client tunneling requests through an HTTP proxy
+    ///
+    /// version should be in `YYYYMMDD` format
+    ///
+    /// ```no_run
+    /// extern crate foursquare;
+    /// extern crate tokio_core;
+    ///
+    /// use tokio_core::reactor::Core;
+    /// use foursquare::{Client, Credentials};
+    /// use foursquare::proxy::Proxy;
+    ///
+    /// fn main() {
+    ///   let core = Core::new().expect("reactor fail");
+    ///   let proxy = Proxy::new("http://proxy.internal:3128")
+    ///     .unwrap()
+    ///     .basic_auth("user", "pass")
+    ///     .no_proxy(vec!["localhost", ".internal"]);
+    ///   let fs = Client::proxied(
+    ///     "YYYYMMDD",
+    ///     Credentials::client("client_id", "client_secret"),
+    ///     &core.handle(),
+    ///     proxy
+    ///   ).unwrap();
+    /// }
+    /// ```
+    pub fn proxied<V>(
+        version: V,
+        credentials: Credentials,
+        handle: &Handle,
+        proxy: Proxy,
+    ) -> Result<Self>
+    where
+        V: Into<String>,
+    {
+        let options = ConnectionOptions {
+            proxy: Some(proxy),
+            ..Default::default()
+        };
+        Self::with_options(version, credentials, handle, &options)
+    }
+}
+
+#[cfg(all(feature = "rustls", not(feature = "tls")))]
+impl Client {
+    /// returns a new client using rustls for tls
+    ///
+    /// version should be in `YYYYMMDD` format
+    pub fn new<V>(version: V, credentials: Credentials, handle: &Handle) -> Self
+    where
+        V: Into<String>,
+    {
+        Self::rustls(version, credentials, handle)
+    }
+
+    /// returns a new client using rustls for tls with custom keep-alive
+    /// settings. See `Client::rustls_with_options`
+    ///
+    /// version should be in `YYYYMMDD` format
+    pub fn with_options<V>(
+        version: V,
+        credentials: Credentials,
+        handle: &Handle,
+        options: &ConnectionOptions,
+    ) -> Result<Self>
+    where
+        V: Into<String>,
+    {
+        Self::rustls_with_options(version, credentials, handle, options)
+    }
+}
+
+#[cfg(feature = "rustls")]
+impl Client {
+    /// returns a new client using rustls for tls, trusting the bundled
+    /// [webpki roots](https://github.com/ctz/webpki-roots)
+    ///
+    /// version should be in `YYYYMMDD` format
+    pub fn rustls<V>(
+        version: V,
+        credentials: Credentials,
+        handle: &Handle,
+    ) -> Self
+    where
+        V: Into<String>,
+    {
+        Self::rustls_with_options(
+            version,
+            credentials,
+            handle,
+            &ConnectionOptions::default(),
+        ).unwrap()
+    }
+
+    /// returns a new client using rustls for tls with custom keep-alive
+    /// settings. Connector settings, `tcp_keepalive`, `nodelay` and `proxy`,
+    /// require the `tls` feature
+    ///
+    /// version should be in `YYYYMMDD` format
+    pub fn rustls_with_options<V>(
+        version: V,
+        credentials: Credentials,
+        handle: &Handle,
+        options: &ConnectionOptions,
+    ) -> Result<Self>
+    where
+        V: Into<String>,
+    {
+        if options.tcp_keepalive.is_some() || options.nodelay ||
+            options.proxy.is_some()
+        {
+            return Err(
+                "tcp_keepalive, nodelay and proxy options require the tls feature"
+                    .into(),
+            );
+        }
+        let connector =
+            hyper_rustls::HttpsConnector::new(options.dns_threads, handle);
+        let http = options
+            .configure(HyperClient::configure())
             .connector(connector)
-            .keep_alive(true)
             .build(handle);
-        Self::custom(version, credentials, http)
+        Ok(Self::custom(version, credentials, http))
     }
 }
 
-impl<C> Client<C>
-where
-    C: Clone + Connect,
-{
-    /// Return a new Client with a custom `hyper::Client`
-    pub fn custom<V>(
+impl Client {
+    /// Return a new Client with a custom `hyper::Client`, for instance one
+    /// with a custom connector
+    pub fn custom<V, C>(
         version: V,
         credentials: Credentials,
         http: HyperClient<C>,
     ) -> Self
     where
         V: Into<String>,
+        C: Clone + Connect,
+    {
+        Self::with_transport(version, credentials, HyperTransport::new(http))
+    }
+
+    /// Return a new Client sending requests with a custom `Transport`
+    pub fn with_transport<V, T>(
+        version: V,
+        credentials: Credentials,
+        transport: T,
+    ) -> Self
+    where
+        V: Into<String>,
+        T: Transport + 'static,
     {
         Self {
             host: DEFAULT_HOST.to_owned(),
             version: version.into(),
-            http: http,
+            transport: Rc::new(transport),
             credentials: credentials,
+            lenient: false,
         }
     }
 
+    /// Skip malformed venues in response lists rather than failing the
+    /// whole response, recording a warning for each on the `Response`. See
+    /// `foursquare::lenient` for details
+    ///
+    /// Lenient responses are parsed into a `serde_json::Value` first and
+    /// each list item is deserialized twice, once to check it and once as
+    /// part of the response, so expect slower parsing than the default
+    pub fn lenient(mut self, lenient: bool) -> Self {
+        self.lenient = lenient;
+        self
+    }
+
     /// Return an interface to venue operations
-    pub fn venues(&self) -> Venues<C> {
+    pub fn venues(&self) -> Venues {
         Venues::new(self.clone())
     }
 
-    fn get<Out>(&self, uri: String) -> Future<Out>
+    fn get<T>(&self, uri: String) -> Future<Response<T>>
     where
-        Out: DeserializeOwned + 'static,
+        T: DeserializeOwned + Lenient + 'static,
     {
-        self.request(Method::Get, uri, None)
+        self.parse(self.get_body(uri))
     }
 
-    fn request<Out>(
+    fn parse<T>(&self, body: Future<Body>) -> Future<Response<T>>
+    where
+        T: DeserializeOwned + Lenient + 'static,
+    {
+        let lenient = self.lenient;
+        Box::new(body.and_then(move |body| if lenient {
+            body.parse_lenient()
+        } else {
+            body.parse()
+        }))
+    }
+
+    fn get_body(&self, uri: String) -> Future<Body> {
+        self.request(transport::Method::Get, uri, None)
+    }
+
+    fn request(
         &self,
-        method: Method,
+        method: transport::Method,
         uri: String,
         body: Option<Vec<u8>>,
-    ) -> Future<Out>
-    where
-        Out: DeserializeOwned + 'static,
-    {
+    ) -> Future<Body> {
         let url = {
             let mut parsed = Url::parse(&uri).unwrap();
             parsed.query_pairs_mut().append_pair(
@@ -215,50 +489,104 @@ where
                     .append_pair("client_id", client_id.as_str())
                     .append_pair("client_secret", client_secret.as_str());
             }
-            parsed.to_string().parse().into_future()
+            parsed
         };
-        let instance = self.clone();
-        let body2 = body.clone();
-        let method2 = method.clone();
-        let response = url.map_err(Error::from).and_then(move |url| {
-            let mut req = Request::new(method2, url);
-
-            if let Some(body) = body2 {
-                req.set_body(body)
+        #[cfg(feature = "compression")]
+        let headers = vec![
+            (
+                "Accept-Encoding".to_owned(),
+                compression::ACCEPT_ENCODING.to_owned(),
+            ),
+        ];
+        #[cfg(not(feature = "compression"))]
+        let headers = Vec::new();
+        let request = transport::Request {
+            method,
+            url,
+            headers,
+            body,
+        };
+        Box::new(self.transport.send(request).and_then(move |response| {
+            #[cfg(feature = "compression")]
+            let response = compression::decode(response)?;
+            debug!("response headers {:?}", response.headers);
+            let status = response.status;
+            let success = response.is_success();
+            let response_body = response.body;
+            // avoid copying large payloads into lossy strings unless they
+            // will be logged
+            if log_enabled!(LogLevel::Debug) {
+                debug!(
+                    "response {} {}",
+                    if success { "payload" } else { "error" },
+                    String::from_utf8_lossy(&response_body)
+                );
+            }
+            if success {
+                Ok(Body { bytes: response_body })
+            } else {
+                Err(
+                    ErrorKind::Fault {
+                        code: StatusCode::try_from(status).unwrap_or(
+                            StatusCode::Unregistered(status),
+                        ),
+                        error: serde_json::from_slice(&response_body)?,
+                    }.into(),
+                )
             }
-            instance.http.request(req).map_err(Error::from)
-        });
-        Box::new(response.and_then(move |response| {
-            debug!("response headers {:?}", response.headers());
-            let status = response.status();
-            Box::new(response.body().concat2().map_err(Error::from).and_then(
-                move |response_body| if status.is_success() {
-                    debug!(
-                        "response payload {}",
-                        String::from_utf8_lossy(&response_body)
-                    );
-                    serde_json::from_slice::<Out>(&response_body).map_err(
-                        |error| {
-                            ErrorKind::Codec(error).into()
-                        },
-                    )
-                } else {
-                    debug!(
-                        "response error {}",
-                        String::from_utf8_lossy(&response_body)
-                    );
-                    Err(
-                        ErrorKind::Fault {
-                            code: status,
-                            error: serde_json::from_slice(&response_body)?,
-                        }.into(),
-                    )
-                },
-            ))
         }))
     }
 }
 
+/// A successful response's buffered json body.
+///
+/// Responses parsed from a `Body` may borrow strings from it rather than
+/// allocating their own, see `venue::borrowed`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    bytes: Vec<u8>,
+}
+
+impl Body {
+    /// Return the raw json bytes of this body
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Deserialize a response, borrowing from this body where the response
+    /// type allows
+    pub fn parse<'a, T>(&'a self) -> Result<Response<T>>
+    where
+        T: Deserialize<'a>,
+    {
+        serde_json::from_slice(&self.bytes).map_err(|error| {
+            ErrorKind::Codec(error).into()
+        })
+    }
+
+    /// Deserialize a response, skipping malformed list items and recording
+    /// a warning for each
+    pub fn parse_lenient<T>(&self) -> Result<Response<T>>
+    where
+        T: DeserializeOwned + Lenient,
+    {
+        let mut value: serde_json::Value = serde_json::from_slice(&self.bytes)?;
+        let mut warnings = Vec::new();
+        if let Some(response) = value.get_mut("response") {
+            T::skip_malformed(response, &mut warnings);
+        }
+        let mut response: Response<T> = serde_json::from_value(value)?;
+        response.warnings = warnings;
+        Ok(response)
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self { bytes }
+    }
+}
+
 // representations
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -276,4 +604,7 @@ pub struct Meta {
 pub struct Response<T> {
     pub meta: Meta,
     pub response: T,
+    /// Malformed list items skipped by a lenient client
+    #[serde(skip)]
+    pub warnings: Vec<Warning>,
 }
\ No newline at end of file
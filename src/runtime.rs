@@ -0,0 +1,81 @@
+//! A reactor running on a background thread
+//!
+//! Shared by the `blocking` and `asynchronous` clients, which submit
+//! operations to a `Runtime` and receive their results from whichever
+//! thread they are waiting on.
+
+use std::sync::mpsc;
+use std::thread;
+
+use futures::{Future as StdFuture, Stream};
+use futures::sync::mpsc as async_mpsc;
+use tokio_core::reactor::{Core, Handle};
+
+use {Client, ErrorKind, Future, Response, Result};
+use venue::Venues;
+
+/// An operation to run on the background reactor
+type Job = Box<FnMut(&Venues) -> Box<StdFuture<Item = (), Error = ()>> + Send>;
+
+/// A handle to a background reactor. The reactor's thread exits when the
+/// last clone of its handle is dropped
+#[derive(Clone)]
+pub(crate) struct Runtime {
+    jobs: async_mpsc::UnboundedSender<Job>,
+}
+
+impl Runtime {
+    /// Start a new reactor thread, running operations with the client
+    /// returned by `build`. `build` is called on the reactor's thread, so
+    /// the client may use transports which aren't `Send`
+    pub fn start<B>(build: B) -> Result<Self>
+    where
+        B: FnOnce(&Handle) -> Client + Send + 'static,
+    {
+        let (jobs, queue) = async_mpsc::unbounded::<Job>();
+        let (started, start) = mpsc::channel();
+        thread::Builder::new()
+            .name("foursquare-runtime".into())
+            .spawn(move || {
+                let mut core = match Core::new() {
+                    Ok(core) => core,
+                    Err(err) => return drop(started.send(Err(err))),
+                };
+                let handle = core.handle();
+                let venues = build(&handle).venues();
+                drop(started.send(Ok(())));
+                // runs until every sender has been dropped
+                drop(core.run(queue.for_each(move |mut job| {
+                    handle.spawn(job(&venues));
+                    Ok(())
+                })))
+            })?;
+        match start.recv() {
+            Ok(started) => started?,
+            _ => return Err(ErrorKind::RuntimeShutdown.into()),
+        }
+        Ok(Self { jobs })
+    }
+
+    /// Run an operation on the reactor, passing its result to `done` on
+    /// the reactor's thread
+    pub fn execute<T, F, D>(&self, operation: F, done: D) -> Result<()>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Venues) -> Future<Response<T>> + Send + 'static,
+        D: FnOnce(Result<Response<T>>) + Send + 'static,
+    {
+        let mut pending = Some((operation, done));
+        let job: Job = Box::new(move |venues| {
+            let (operation, done) =
+                pending.take().expect("runtime job run more than once");
+            Box::new(operation(venues).then(move |result| {
+                done(result);
+                Ok(())
+            }))
+        });
+        self.jobs.unbounded_send(job).map_err(|_| {
+            ErrorKind::RuntimeShutdown.into()
+        })
+    }
+}
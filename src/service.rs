@@ -0,0 +1,685 @@
+//! [tower](https://github.com/tower-rs/tower) integration
+//!
+//! Any `tower_service::Service` accepting `transport::Request`s and
+//! resolving to `transport::Response`s may be used as a `Client`'s request
+//! pipeline with `Client::with_service`, so standard tower layers such as
+//! timeouts, concurrency limits and tracing can wrap api traffic. Wrap a
+//! `Transport` in a `TransportService` to form the innermost service.
+//!
+//! Layers for api traffic are provided too: `RetryLayer` retries failed
+//! requests, `RateLimitLayer` spaces requests to stay within a rate limit
+//! and `CacheLayer` answers repeated `GET` requests from memory.
+//!
+//! ```
+//! extern crate foursquare;
+//! extern crate tokio_core;
+//! extern crate tower_layer;
+//!
+//! use tokio_core::reactor::Core;
+//! use tower_layer::Layer;
+//! use foursquare::{Client, Credentials};
+//! use foursquare::service::{RetryLayer, TransportService};
+//! use foursquare::transport::MemoryTransport;
+//!
+//! fn main() {
+//!   let core = Core::new().expect("reactor fail");
+//!   let service = RetryLayer::new(2, &core.handle()).layer(
+//!     TransportService::new(MemoryTransport::new())
+//!   );
+//!   let fs = Client::with_service(
+//!     "YYYYMMDD",
+//!     Credentials::client("client_id", "client_secret"),
+//!     service
+//!   );
+//! }
+//! ```
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use futures::{Async, Future as StdFuture, Poll};
+use futures::future;
+use tokio_core::reactor::{Handle, Timeout};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use {Client, Credentials, Error, Future};
+use transport::{Method, Request, Response, Transport};
+
+/// A `Service` sending requests with a `Transport`
+#[derive(Debug, Clone)]
+pub struct TransportService<T> {
+    transport: T,
+}
+
+impl<T> TransportService<T>
+where
+    T: Transport,
+{
+    /// Return a new service sending requests with the provided transport
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+}
+
+impl<T> Service<Request> for TransportService<T>
+where
+    T: Transport,
+{
+    type Response = Response;
+    type Error = Error;
+    type Future = Future<Response>;
+
+    fn poll_ready(&mut self) -> Poll<(), Error> {
+        Ok(Async::Ready(()))
+    }
+
+    fn call(&mut self, request: Request) -> Future<Response> {
+        self.transport.send(request)
+    }
+}
+
+/// Return a future which waits for a service to be ready before resolving
+/// to it
+fn ready<S>(service: S) -> Box<StdFuture<Item = S, Error = Error>>
+where
+    S: Service<Request> + 'static,
+    S::Error: Into<Error>,
+{
+    let mut service = Some(service);
+    Box::new(future::poll_fn(move || {
+        let ready = service
+            .as_mut()
+            .expect("polled after ready")
+            .poll_ready()
+            .map_err(Into::into)?;
+        Ok(match ready {
+            Async::Ready(()) => {
+                Async::Ready(service.take().expect("polled after ready"))
+            }
+            _ => Async::NotReady,
+        })
+    }))
+}
+
+/// A `Transport` sending requests through a `Service`
+struct ServiceTransport<S> {
+    service: Rc<RefCell<S>>,
+}
+
+impl<S> Transport for ServiceTransport<S>
+where
+    S: Service<Request, Response = Response> + 'static,
+    S::Error: Into<Error>,
+    S::Future: 'static,
+{
+    fn send(&self, request: Request) -> Future<Response> {
+        let service = self.service.clone();
+        Box::new(ready(Shared(service)).and_then(move |mut service| {
+            service.call(request).map_err(Into::into)
+        }))
+    }
+}
+
+/// A handle to a service shared between in flight requests
+struct Shared<S>(Rc<RefCell<S>>);
+
+impl<S> Service<Request> for Shared<S>
+where
+    S: Service<Request>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self) -> Poll<(), S::Error> {
+        self.0.borrow_mut().poll_ready()
+    }
+
+    fn call(&mut self, request: Request) -> S::Future {
+        self.0.borrow_mut().call(request)
+    }
+}
+
+impl Client {
+    /// Return a new Client sending requests through a tower `Service`
+    pub fn with_service<V, S>(
+        version: V,
+        credentials: Credentials,
+        service: S,
+    ) -> Self
+    where
+        V: Into<String>,
+        S: Service<Request, Response = Response> + 'static,
+        S::Error: Into<Error>,
+        S::Future: 'static,
+    {
+        Self::with_transport(
+            version,
+            credentials,
+            ServiceTransport { service: Rc::new(RefCell::new(service)) },
+        )
+    }
+}
+
+/// A `Layer` retrying idempotent requests which fail or receive a 5xx or
+/// 429 response
+///
+/// Retries are delayed by an exponential backoff, doubling from 100
+/// milliseconds by default, or by a 429 response's `Retry-After` seconds.
+/// Only `GET` requests are retried
+#[derive(Debug, Clone)]
+pub struct RetryLayer {
+    retries: u32,
+    backoff: Duration,
+    handle: Handle,
+}
+
+impl RetryLayer {
+    /// Return a new layer retrying each request at most `retries` times,
+    /// scheduling retries on the provided reactor
+    pub fn new(retries: u32, handle: &Handle) -> Self {
+        Self {
+            retries,
+            backoff: Duration::from_millis(100),
+            handle: handle.clone(),
+        }
+    }
+
+    /// Set the delay before the first retry, which doubles for each
+    /// following retry
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+}
+
+impl<S> Layer<S> for RetryLayer {
+    type Service = Retry<S>;
+
+    fn layer(&self, inner: S) -> Retry<S> {
+        Retry {
+            inner,
+            retries: self.retries,
+            backoff: self.backoff,
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+/// A `Service` retrying idempotent requests which fail or receive a 5xx or
+/// 429 response. See `RetryLayer`
+#[derive(Debug, Clone)]
+pub struct Retry<S> {
+    inner: S,
+    retries: u32,
+    backoff: Duration,
+    handle: Handle,
+}
+
+impl<S> Service<Request> for Retry<S>
+where
+    S: Service<Request, Response = Response> + Clone + 'static,
+    S::Error: Into<Error>,
+    S::Future: 'static,
+{
+    type Response = Response;
+    type Error = Error;
+    type Future = Future<Response>;
+
+    fn poll_ready(&mut self) -> Poll<(), Error> {
+        self.inner.poll_ready().map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request) -> Future<Response> {
+        let mut retry = self.clone();
+        // a failed request which isn't idempotent may still have been
+        // applied, so sending it again could repeat its effects
+        if request.method != Method::Get {
+            retry.retries = 0;
+        }
+        attempt(retry, request, 0)
+    }
+}
+
+impl<S> Retry<S> {
+    /// The delay before retrying a request already retried `attempts`
+    /// times, which failed with `response` if it received one
+    fn delay(&self, response: Option<&Response>, attempts: u32) -> Duration {
+        response.and_then(retry_after).unwrap_or_else(|| {
+            self.backoff * 2u32.saturating_pow(attempts)
+        })
+    }
+}
+
+fn attempt<S>(
+    retry: Retry<S>,
+    request: Request,
+    attempts: u32,
+) -> Future<Response>
+where
+    S: Service<Request, Response = Response> + Clone + 'static,
+    S::Error: Into<Error>,
+    S::Future: 'static,
+{
+    Box::new(ready(retry.inner.clone()).and_then(move |mut service| {
+        let next = request.clone();
+        service.call(request).map_err(Into::into).then(
+            move |result| -> Future<Response> {
+                let failed = match result {
+                    Ok(ref response) => {
+                        response.status >= 500 || response.status == 429
+                    }
+                    _ => true,
+                };
+                if !failed || attempts >= retry.retries {
+                    return Box::new(future::result(result));
+                }
+                let delay = retry.delay(result.as_ref().ok(), attempts);
+                debug!("retrying {} in {:?}", next.url.path(), delay);
+                match Timeout::new(delay, &retry.handle) {
+                    Ok(timeout) => {
+                        Box::new(timeout.from_err().and_then(move |_| {
+                            attempt(retry, next, attempts + 1)
+                        }))
+                    }
+                    Err(err) => Box::new(future::err(err.into())),
+                }
+            },
+        )
+    }))
+}
+
+/// The delay requested by a 429 response's `Retry-After` header, if given
+/// in seconds
+fn retry_after(response: &Response) -> Option<Duration> {
+    if response.status != 429 {
+        return None;
+    }
+    response
+        .header("Retry-After")
+        .and_then(|value| value.trim().parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// A `Layer` spacing requests evenly so that at most `requests` are sent
+/// in any `per` period
+///
+/// Requests beyond the limit are delayed rather than rejected. Services
+/// layered by the same `RateLimitLayer` have separate limits
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    interval: Duration,
+    handle: Handle,
+}
+
+impl RateLimitLayer {
+    /// Return a new layer sending at most `requests` requests each `per`
+    /// period, scheduling delayed requests on the provided reactor
+    pub fn new(requests: u32, per: Duration, handle: &Handle) -> Self {
+        Self {
+            interval: per / requests.max(1),
+            handle: handle.clone(),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> RateLimit<S> {
+        RateLimit {
+            inner,
+            interval: self.interval,
+            next: Rc::new(Cell::new(Instant::now())),
+            handle: self.handle.clone(),
+        }
+    }
+}
+
+/// A `Service` spacing requests to stay within a rate limit. See
+/// `RateLimitLayer`
+#[derive(Debug, Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    interval: Duration,
+    next: Rc<Cell<Instant>>,
+    handle: Handle,
+}
+
+impl<S> Service<Request> for RateLimit<S>
+where
+    S: Service<Request, Response = Response> + Clone + 'static,
+    S::Error: Into<Error>,
+    S::Future: 'static,
+{
+    type Response = Response;
+    type Error = Error;
+    type Future = Future<Response>;
+
+    fn poll_ready(&mut self) -> Poll<(), Error> {
+        self.inner.poll_ready().map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request) -> Future<Response> {
+        let now = Instant::now();
+        let slot = ::std::cmp::max(now, self.next.get());
+        self.next.set(slot + self.interval);
+        if slot <= now {
+            return Box::new(self.inner.call(request).map_err(Into::into));
+        }
+        let inner = self.inner.clone();
+        match Timeout::new_at(slot, &self.handle) {
+            Ok(timeout) => Box::new(
+                timeout
+                    .from_err()
+                    .and_then(move |_| ready(inner))
+                    .and_then(move |mut service| {
+                        service.call(request).map_err(Into::into)
+                    }),
+            ),
+            Err(err) => Box::new(future::err(err.into())),
+        }
+    }
+}
+
+/// A `Layer` answering `GET` requests from memory with the 2xx response
+/// last received for the same url, for up to `ttl`
+///
+/// Expired responses are evicted when a new response is stored. Services
+/// layered by the same `CacheLayer` have separate caches
+#[derive(Debug, Clone)]
+pub struct CacheLayer {
+    ttl: Duration,
+}
+
+impl CacheLayer {
+    /// Return a new layer caching responses for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl }
+    }
+}
+
+impl<S> Layer<S> for CacheLayer {
+    type Service = Cache<S>;
+
+    fn layer(&self, inner: S) -> Cache<S> {
+        Cache {
+            inner,
+            ttl: self.ttl,
+            responses: Rc::new(RefCell::new(HashMap::new())),
+        }
+    }
+}
+
+/// A `Service` answering repeated `GET` requests from memory. See
+/// `CacheLayer`
+#[derive(Debug, Clone)]
+pub struct Cache<S> {
+    inner: S,
+    ttl: Duration,
+    responses: Rc<RefCell<HashMap<String, (Instant, Response)>>>,
+}
+
+impl<S> Service<Request> for Cache<S>
+where
+    S: Service<Request, Response = Response> + 'static,
+    S::Error: Into<Error>,
+    S::Future: 'static,
+{
+    type Response = Response;
+    type Error = Error;
+    type Future = Future<Response>;
+
+    fn poll_ready(&mut self) -> Poll<(), Error> {
+        self.inner.poll_ready().map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Request) -> Future<Response> {
+        if request.method != Method::Get {
+            return Box::new(self.inner.call(request).map_err(Into::into));
+        }
+        let key = request.url.as_str().to_owned();
+        let ttl = self.ttl;
+        let cached = match self.responses.borrow().get(&key) {
+            Some(&(stored, ref response)) if stored.elapsed() < ttl => {
+                Some(response.clone())
+            }
+            _ => None,
+        };
+        if let Some(response) = cached {
+            return Box::new(future::ok(response));
+        }
+        let responses = self.responses.clone();
+        Box::new(self.inner.call(request).map_err(Into::into).map(
+            move |response| {
+                if response.status >= 200 && response.status < 300 {
+                    let mut responses = responses.borrow_mut();
+                    responses.retain(|_, &mut (stored, _)| {
+                        stored.elapsed() < ttl
+                    });
+                    responses.insert(key, (Instant::now(), response.clone()));
+                }
+                response
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio_core::reactor::Core;
+    use url::Url;
+
+    use venue::HoursOptions;
+
+    /// responds with `status` until called `failures` times
+    #[derive(Clone)]
+    struct Flaky {
+        calls: Rc<Cell<usize>>,
+        failures: usize,
+        status: u16,
+        headers: Vec<(String, String)>,
+    }
+
+    impl Flaky {
+        fn new(failures: usize, status: u16) -> Self {
+            Flaky {
+                calls: Rc::new(Cell::new(0)),
+                failures,
+                status,
+                headers: Vec::new(),
+            }
+        }
+    }
+
+    impl Service<Request> for Flaky {
+        type Response = Response;
+        type Error = Error;
+        type Future = Future<Response>;
+
+        fn poll_ready(&mut self) -> Poll<(), Error> {
+            Ok(Async::Ready(()))
+        }
+
+        fn call(&mut self, _: Request) -> Future<Response> {
+            let calls = self.calls.get() + 1;
+            self.calls.set(calls);
+            let (status, headers, body) = if calls > self.failures {
+                (
+                    200,
+                    Vec::new(),
+                    r#"{"meta": {"code": 200, "requestId": "1"},
+                        "response": {"hours": {"timeframes": []},
+                                     "popular": {"timeframes": []}}}"#,
+                )
+            } else {
+                (
+                    self.status,
+                    self.headers.clone(),
+                    r#"{"meta": {"code": 503, "requestId": "1",
+                                 "errorDetail": "unavailable"},
+                        "response": {}}"#,
+                )
+            };
+            Box::new(future::ok(Response {
+                status,
+                headers,
+                body: body.as_bytes().to_vec(),
+            }))
+        }
+    }
+
+    fn layer(retries: u32, core: &Core) -> RetryLayer {
+        RetryLayer::new(retries, &core.handle())
+            .backoff(Duration::from_millis(1))
+    }
+
+    #[test]
+    fn retries_server_errors() {
+        let mut core = Core::new().unwrap();
+        let flaky = Flaky::new(2, 503);
+        let fs = Client::with_service(
+            "20170801",
+            Credentials::user("token"),
+            layer(2, &core).layer(flaky.clone()),
+        );
+        let hours = core.run(fs.venues().hours("1", &HoursOptions::default()));
+        assert!(hours.is_ok());
+        assert_eq!(flaky.calls.get(), 3);
+
+        flaky.calls.set(0);
+        let fs = Client::with_service(
+            "20170801",
+            Credentials::user("token"),
+            layer(1, &core).layer(flaky.clone()),
+        );
+        let hours = core.run(fs.venues().hours("1", &HoursOptions::default()));
+        assert!(hours.is_err());
+        assert_eq!(flaky.calls.get(), 2)
+    }
+
+    fn get(path: &str) -> Request {
+        Request {
+            method: Method::Get,
+            url: Url::parse(&format!("https://api.foursquare.com{}", path))
+                .unwrap(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    #[test]
+    fn retries_rate_limited_requests() {
+        let mut core = Core::new().unwrap();
+        let mut flaky = Flaky::new(1, 429);
+        flaky.headers = vec![("Retry-After".into(), "0".into())];
+        let mut retry = layer(1, &core).layer(flaky.clone());
+        let response = core.run(retry.call(get("/v2/venues/1/hours")))
+            .unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(flaky.calls.get(), 2)
+    }
+
+    #[test]
+    fn delays_retries() {
+        let core = Core::new().unwrap();
+        let retry = RetryLayer::new(3, &core.handle())
+            .layer(Flaky::new(0, 200));
+        let limited = Response {
+            status: 429,
+            headers: vec![("Retry-After".into(), "30".into())],
+            body: Vec::new(),
+        };
+        assert_eq!(retry.delay(Some(&limited), 0), Duration::from_secs(30));
+        assert_eq!(retry.delay(None, 0), Duration::from_millis(100));
+        assert_eq!(retry.delay(None, 2), Duration::from_millis(400))
+    }
+
+    #[test]
+    fn does_not_retry_posts() {
+        let mut core = Core::new().unwrap();
+        let flaky = Flaky::new(1, 503);
+        let mut retry = layer(2, &core).layer(flaky.clone());
+        let request = Request {
+            method: Method::Post,
+            ..get("/v2/venues/add")
+        };
+        let response = core.run(retry.call(request)).unwrap();
+        assert_eq!(response.status, 503);
+        assert_eq!(flaky.calls.get(), 1)
+    }
+
+    #[test]
+    fn rate_limits_requests() {
+        let mut core = Core::new().unwrap();
+        let flaky = Flaky::new(0, 200);
+        let mut limited =
+            RateLimitLayer::new(2, Duration::from_millis(50), &core.handle())
+                .layer(flaky.clone());
+        let started = Instant::now();
+        let requests = (0..3)
+            .map(|_| limited.call(get("/v2/venues/1/hours")))
+            .collect::<Vec<_>>();
+        core.run(future::join_all(requests)).unwrap();
+        assert_eq!(flaky.calls.get(), 3);
+        assert!(started.elapsed() >= Duration::from_millis(50))
+    }
+
+    #[test]
+    fn caches_get_responses() {
+        let mut core = Core::new().unwrap();
+        let flaky = Flaky::new(0, 200);
+        let mut cache = CacheLayer::new(Duration::from_secs(60))
+            .layer(flaky.clone());
+        for _ in 0..2 {
+            let response = core.run(cache.call(get("/v2/venues/1/hours")))
+                .unwrap();
+            assert_eq!(response.status, 200);
+        }
+        assert_eq!(flaky.calls.get(), 1);
+        core.run(cache.call(get("/v2/venues/2/hours"))).unwrap();
+        assert_eq!(flaky.calls.get(), 2);
+
+        for _ in 0..2 {
+            let request = Request {
+                method: Method::Post,
+                ..get("/v2/venues/1/hours")
+            };
+            core.run(cache.call(request)).unwrap();
+        }
+        assert_eq!(flaky.calls.get(), 4)
+    }
+
+    #[test]
+    fn does_not_cache_failures_or_expired_responses() {
+        let mut core = Core::new().unwrap();
+        let flaky = Flaky::new(1, 503);
+        let mut cache = CacheLayer::new(Duration::from_secs(60))
+            .layer(flaky.clone());
+        for _ in 0..3 {
+            core.run(cache.call(get("/v2/venues/1/hours"))).unwrap();
+        }
+        assert_eq!(flaky.calls.get(), 2);
+
+        let flaky = Flaky::new(0, 200);
+        let mut cache = CacheLayer::new(Duration::from_secs(0))
+            .layer(flaky.clone());
+        for _ in 0..2 {
+            core.run(cache.call(get("/v2/venues/1/hours"))).unwrap();
+        }
+        assert_eq!(flaky.calls.get(), 2)
+    }
+
+    #[test]
+    fn boxed_errors_keep_their_cause() {
+        let boxed: Box<::std::error::Error + Send + Sync> = "refused".into();
+        let error = Error::from(boxed);
+        assert_eq!(error.to_string(), "service error: refused");
+        assert_eq!(error.iter().nth(1).unwrap().to_string(), "refused")
+    }
+}
@@ -0,0 +1,827 @@
+//! Local SQLite mirror of venues and categories
+//!
+//! A `Store` keeps normalized copies of venues, their locations,
+//! categories, hours, photos and tips in a SQLite database keyed by venue id,
+//! recording when each venue was last seen in a response and last refreshed
+//! from the api. `refresh` re-fetches venues whose data has gone stale.
+//!
+//! ```no_run
+//! extern crate chrono;
+//! extern crate foursquare;
+//! extern crate tokio_core;
+//!
+//! use std::rc::Rc;
+//!
+//! use chrono::{Duration, Utc};
+//! use tokio_core::reactor::Core;
+//! use foursquare::{Client, Credentials};
+//! use foursquare::sqlite::{self, Store};
+//!
+//! fn main() {
+//!   let mut core = Core::new().expect("reactor fail");
+//!   let fs = Client::new(
+//!     "YYYYMMDD",
+//!     Credentials::client("client_id", "client_secret"),
+//!     &core.handle()
+//!   );
+//!   let store = Rc::new(Store::open("venues.db").unwrap());
+//!   let refreshed = core.run(sqlite::refresh(
+//!     store,
+//!     fs.venues(),
+//!     Utc::now() - Duration::days(7),
+//!     4
+//!   )).unwrap();
+//!   println!(
+//!     "refreshed {} venues, {} failed",
+//!     refreshed.refreshed,
+//!     refreshed.failed
+//!   );
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures::{Future as StdFuture, Stream};
+use futures::stream;
+use rusqlite::{self, Connection};
+use rusqlite::types::ToSql;
+use serde_json;
+
+use {Future, Result};
+use venue::{CategoryTree, HoursOptions, Tip, Tips, Venue,
+            VenueDetailsOptions, VenueHours, VenueHoursResponse, Venues};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS venues (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    url TEXT,
+    rating REAL,
+    price_tier INTEGER,
+    time_zone TEXT,
+    data TEXT NOT NULL,
+    last_seen INTEGER NOT NULL,
+    refreshed_at INTEGER,
+    refresh_attempted_at INTEGER
+);
+CREATE TABLE IF NOT EXISTS locations (
+    venue_id TEXT PRIMARY KEY REFERENCES venues(id) ON DELETE CASCADE,
+    lat REAL NOT NULL,
+    lng REAL NOT NULL,
+    address TEXT,
+    cross_street TEXT,
+    city TEXT,
+    state TEXT,
+    postal_code TEXT,
+    cc TEXT,
+    country TEXT NOT NULL,
+    formatted_address TEXT
+);
+CREATE INDEX IF NOT EXISTS locations_lat_lng ON locations (lat, lng);
+CREATE TABLE IF NOT EXISTS categories (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    plural_name TEXT NOT NULL,
+    short_name TEXT NOT NULL,
+    icon_prefix TEXT NOT NULL,
+    icon_suffix TEXT NOT NULL,
+    parent_id TEXT
+);
+CREATE TABLE IF NOT EXISTS venue_categories (
+    venue_id TEXT NOT NULL REFERENCES venues(id) ON DELETE CASCADE,
+    category_id TEXT NOT NULL,
+    is_primary INTEGER NOT NULL,
+    PRIMARY KEY (venue_id, category_id)
+);
+CREATE TABLE IF NOT EXISTS timeframes (
+    venue_id TEXT NOT NULL REFERENCES venues(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL,
+    day INTEGER NOT NULL,
+    start TEXT NOT NULL,
+    end TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS timeframes_venue ON timeframes (venue_id);
+CREATE TABLE IF NOT EXISTS photos (
+    id TEXT PRIMARY KEY,
+    venue_id TEXT NOT NULL REFERENCES venues(id) ON DELETE CASCADE,
+    prefix TEXT NOT NULL,
+    suffix TEXT NOT NULL,
+    width INTEGER NOT NULL,
+    height INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS tips (
+    id TEXT PRIMARY KEY,
+    venue_id TEXT NOT NULL REFERENCES venues(id) ON DELETE CASCADE,
+    text TEXT NOT NULL,
+    canonical_url TEXT NOT NULL
+);
+";
+
+/// A SQLite database of venues
+pub struct Store {
+    conn: RefCell<Connection>,
+}
+
+impl Store {
+    /// Open or create a database at the provided path
+    pub fn open<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        Self::init(Connection::open(path)?)
+    }
+
+    /// Create a new in-memory database
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
+
+    fn init(conn: Connection) -> Result<Self> {
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: RefCell::new(conn) })
+    }
+
+    /// Replace stored categories with those of a category tree
+    pub fn save_categories(&self, tree: &CategoryTree) -> Result<()> {
+        let mut conn = self.conn.borrow_mut();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM categories", &[])?;
+        for category in tree.flatten() {
+            let parent = tree.parent(&category.id).map(|p| p.id.clone());
+            tx.execute(
+                "INSERT INTO categories VALUES (?, ?, ?, ?, ?, ?, ?)",
+                &[
+                    &category.id,
+                    &category.name,
+                    &category.plural_name,
+                    &category.short_name,
+                    &category.icon.prefix,
+                    &category.icon.suffix,
+                    &parent as &ToSql,
+                ],
+            )?;
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Save a venue seen at the provided time, merging it with any stored
+    /// record of the same venue
+    pub fn save_venue(&self, venue: &Venue, seen: DateTime<Utc>) -> Result<()> {
+        self.save(venue.clone(), seen, None)
+    }
+
+    /// Save a venue fetched from the details api at the provided time,
+    /// marking it as refreshed. Tips included with the venue replace those
+    /// stored
+    pub fn save_details(
+        &self,
+        venue: &Venue,
+        fetched: DateTime<Utc>,
+    ) -> Result<()> {
+        self.save(venue.clone(), fetched, Some(fetched))
+    }
+
+    fn save(
+        &self,
+        venue: Venue,
+        seen: DateTime<Utc>,
+        refreshed: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        // only venue details carry tips, and a merged venue may carry tips
+        // older than those saved with `save_tips`
+        let tips = match refreshed {
+            Some(_) => venue.tips.clone(),
+            _ => None,
+        };
+        let venue = match self.venue(&venue.id)? {
+            Some(mut existing) => {
+                existing.merge(venue);
+                existing
+            }
+            _ => venue,
+        };
+        let mut conn = self.conn.borrow_mut();
+        let tx = conn.transaction()?;
+        let data = serde_json::to_string(&venue)?;
+        let rating = venue.rating.map(|r| r as f64);
+        let price_tier = venue.price.as_ref().map(|p| p.tier as i64);
+        let refreshed = refreshed.map(|r| r.timestamp());
+        // update in place rather than replace, which would cascade to the
+        // venue's hours, photos and tips
+        let updated = tx.execute(
+            "UPDATE venues SET name = ?2, url = ?3, rating = ?4,
+             price_tier = ?5, time_zone = ?6, data = ?7, last_seen = ?8,
+             refreshed_at = coalesce(?9, refreshed_at)
+             WHERE id = ?1",
+            &[
+                &venue.id,
+                &venue.name,
+                &venue.url,
+                &rating,
+                &price_tier,
+                &venue.time_zone,
+                &data,
+                &seen.timestamp(),
+                &refreshed as &ToSql,
+            ],
+        )?;
+        if updated == 0 {
+            tx.execute(
+                "INSERT INTO venues VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, NULL)",
+                &[
+                    &venue.id,
+                    &venue.name,
+                    &venue.url,
+                    &rating,
+                    &price_tier,
+                    &venue.time_zone,
+                    &data,
+                    &seen.timestamp(),
+                    &refreshed as &ToSql,
+                ],
+            )?;
+        }
+        let location = &venue.location;
+        tx.execute(
+            "INSERT OR REPLACE INTO locations VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            &[
+                &venue.id,
+                &location.lat,
+                &location.lng,
+                &location.address,
+                &location.cross_street,
+                &location.city,
+                &location.state,
+                &location.postal_code,
+                &location.cc,
+                &location.country,
+                &location.formatted_address.as_ref().map(
+                    |lines| lines.join("\n"),
+                ) as &ToSql,
+            ],
+        )?;
+        tx.execute(
+            "DELETE FROM venue_categories WHERE venue_id = ?",
+            &[&venue.id],
+        )?;
+        let primary = venue.primary_category().map(|c| c.id.clone());
+        for category in &venue.categories {
+            tx.execute(
+                "INSERT OR IGNORE INTO venue_categories VALUES (?, ?, ?)",
+                &[
+                    &venue.id,
+                    &category.id,
+                    &(primary.as_ref() == Some(&category.id)) as &ToSql,
+                ],
+            )?;
+        }
+        let photos = venue
+            .photos
+            .iter()
+            .flat_map(|photos| &photos.groups)
+            .flat_map(|group| &group.items)
+            .chain(&venue.best_photo);
+        tx.execute("DELETE FROM photos WHERE venue_id = ?", &[&venue.id])?;
+        for photo in photos {
+            tx.execute(
+                "INSERT OR REPLACE INTO photos VALUES (?, ?, ?, ?, ?, ?)",
+                &[
+                    &photo.id,
+                    &venue.id,
+                    &photo.prefix,
+                    &photo.suffix,
+                    &(photo.width as i64),
+                    &(photo.height as i64) as &ToSql,
+                ],
+            )?;
+        }
+        if let Some(tips) = tips {
+            tx.execute("DELETE FROM tips WHERE venue_id = ?", &[&venue.id])?;
+            for tip in tips.groups.iter().flat_map(|group| &group.items) {
+                tx.execute(
+                    "INSERT OR REPLACE INTO tips VALUES (?, ?, ?, ?)",
+                    &[
+                        &tip.id,
+                        &venue.id,
+                        &tip.text,
+                        &tip.canonical_url as &ToSql,
+                    ],
+                )?;
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Replace the stored hours of a venue
+    pub fn save_hours(
+        &self,
+        venue_id: &str,
+        hours: &VenueHoursResponse,
+    ) -> Result<()> {
+        let mut conn = self.conn.borrow_mut();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM timeframes WHERE venue_id = ?", &[&venue_id])?;
+        for &(kind, hours) in &[("hours", &hours.hours), ("popular", &hours.popular)] {
+            for frame in &hours.timeframes {
+                for day in &frame.days {
+                    for window in &frame.open {
+                        tx.execute(
+                            "INSERT INTO timeframes VALUES (?, ?, ?, ?, ?)",
+                            &[
+                                &venue_id,
+                                &kind,
+                                &(*day as i64),
+                                &window.start,
+                                &window.end as &ToSql,
+                            ],
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Replace the stored tips of a venue
+    pub fn save_tips(&self, venue_id: &str, tips: &Tips) -> Result<()> {
+        let mut conn = self.conn.borrow_mut();
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM tips WHERE venue_id = ?", &[&venue_id])?;
+        for tip in &tips.items {
+            tx.execute(
+                "INSERT OR REPLACE INTO tips VALUES (?, ?, ?, ?)",
+                &[&tip.id, &venue_id, &tip.text, &tip.canonical_url as &ToSql],
+            )?;
+        }
+        Ok(tx.commit()?)
+    }
+
+    /// Return the stored venue with the provided id
+    pub fn venue(&self, id: &str) -> Result<Option<Venue>> {
+        let data = optional(self.conn.borrow().query_row(
+            "SELECT data FROM venues WHERE id = ?",
+            &[&id],
+            |row| row.get::<_, String>(0),
+        ))?;
+        Ok(match data {
+            Some(data) => Some(serde_json::from_str(&data)?),
+            _ => None,
+        })
+    }
+
+    /// Return the stored hours of a venue
+    pub fn hours(&self, venue_id: &str) -> Result<Option<VenueHoursResponse>> {
+        let conn = self.conn.borrow();
+        let mut statement = conn.prepare(
+            "SELECT kind, day, start, end FROM timeframes
+             WHERE venue_id = ? ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(&[&venue_id], |row| {
+            (
+                row.get::<_, String>(0),
+                row.get::<_, i64>(1),
+                row.get::<_, String>(2),
+                row.get::<_, String>(3),
+            )
+        })?;
+        let mut hours = VenueHoursResponse {
+            hours: VenueHours { timeframes: Vec::new() },
+            popular: VenueHours { timeframes: Vec::new() },
+        };
+        let mut found = false;
+        for row in rows {
+            let (kind, day, start, end) = row?;
+            found = true;
+            let target = if kind == "popular" {
+                &mut hours.popular
+            } else {
+                &mut hours.hours
+            };
+            target.push(day as u16, start, end);
+        }
+        Ok(if found { Some(hours) } else { None })
+    }
+
+    /// Return the stored tips of a venue, without their photos
+    pub fn tips(&self, venue_id: &str) -> Result<Vec<Tip>> {
+        let conn = self.conn.borrow();
+        let mut statement = conn.prepare(
+            "SELECT id, text, canonical_url FROM tips
+             WHERE venue_id = ? ORDER BY rowid",
+        )?;
+        let rows = statement.query_map(&[&venue_id], |row| {
+            Tip {
+                id: row.get(0),
+                text: row.get(1),
+                canonical_url: row.get(2),
+                photo: None,
+            }
+        })?;
+        let mut tips = Vec::new();
+        for tip in rows {
+            tips.push(tip?);
+        }
+        Ok(tips)
+    }
+
+    /// Return stored venues whose name contains the provided text
+    pub fn search(&self, name: &str) -> Result<Vec<Venue>> {
+        self.venues(
+            "SELECT data FROM venues WHERE name LIKE '%' || ? || '%'
+             ORDER BY name",
+            &[&name],
+        )
+    }
+
+    /// Return stored venues of a category
+    pub fn in_category(&self, category_id: &str) -> Result<Vec<Venue>> {
+        self.venues(
+            "SELECT data FROM venues v
+             JOIN venue_categories c ON c.venue_id = v.id
+             WHERE c.category_id = ? ORDER BY v.name",
+            &[&category_id],
+        )
+    }
+
+    fn venues(&self, sql: &str, params: &[&ToSql]) -> Result<Vec<Venue>> {
+        let conn = self.conn.borrow();
+        let mut statement = conn.prepare(sql)?;
+        let rows = statement.query_map(params, |row| row.get::<_, String>(0))?;
+        let mut venues = Vec::new();
+        for data in rows {
+            venues.push(serde_json::from_str(&data?)?);
+        }
+        Ok(venues)
+    }
+
+    /// Return the ids of venues which have not been refreshed since the
+    /// provided time, those never or least recently attempted first so that
+    /// venues which fail to refresh don't hold up others
+    pub fn stale(&self, since: DateTime<Utc>) -> Result<Vec<String>> {
+        let conn = self.conn.borrow();
+        let mut statement = conn.prepare(
+            "SELECT id FROM venues
+             WHERE refreshed_at IS NULL OR refreshed_at < ?
+             ORDER BY refresh_attempted_at, refreshed_at",
+        )?;
+        let rows =
+            statement.query_map(&[&since.timestamp()], |row| row.get(0))?;
+        let mut ids = Vec::new();
+        for id in rows {
+            ids.push(id?);
+        }
+        Ok(ids)
+    }
+
+    /// Record an attempt to refresh a venue, successful or not
+    fn attempted(&self, id: &str, at: DateTime<Utc>) -> Result<()> {
+        self.conn.borrow().execute(
+            "UPDATE venues SET refresh_attempted_at = ? WHERE id = ?",
+            &[&at.timestamp(), &id as &ToSql],
+        )?;
+        Ok(())
+    }
+
+    /// Return when a venue was last seen and last refreshed
+    pub fn last_seen(
+        &self,
+        id: &str,
+    ) -> Result<Option<(DateTime<Utc>, Option<DateTime<Utc>>)>> {
+        Ok(optional(self.conn.borrow().query_row(
+                "SELECT last_seen, refreshed_at FROM venues WHERE id = ?",
+                &[&id],
+                |row| {
+                    (
+                        Utc.timestamp(row.get(0), 0),
+                        row.get::<_, Option<i64>>(1).map(
+                            |secs| Utc.timestamp(secs, 0),
+                        ),
+                    )
+                },
+            ))?)
+    }
+}
+
+/// treat a query returning no rows as an absent value
+fn optional<T>(result: rusqlite::Result<T>) -> rusqlite::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+impl VenueHours {
+    fn push(&mut self, day: u16, start: String, end: String) {
+        use venue::{TimeWindow, Timeframe};
+        let window = TimeWindow { start, end };
+        // group windows sharing a day back into a single timeframe
+        if let Some(frame) = self.timeframes.iter_mut().find(
+            |frame| frame.days == [day],
+        )
+        {
+            frame.open.push(window);
+            return;
+        }
+        self.timeframes.push(Timeframe {
+            days: vec![day],
+            includes_today: None,
+            open: vec![window],
+        })
+    }
+}
+
+/// The outcome of a `refresh`
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Refreshed {
+    /// The number of venues refreshed
+    pub refreshed: usize,
+    /// The number of venues which could not be fetched or saved
+    pub failed: usize,
+}
+
+/// Re-fetch the details and hours of each venue not refreshed since the
+/// provided time, fetching up to `concurrency` venues at once
+///
+/// A venue which fails to refresh, for instance because it no longer
+/// exists, is logged and counted as failed without stopping the others,
+/// and is tried after other stale venues next time.
+pub fn refresh(
+    store: Rc<Store>,
+    venues: Venues,
+    since: DateTime<Utc>,
+    concurrency: usize,
+) -> Future<Refreshed> {
+    let stale = match store.stale(since) {
+        Ok(stale) => stale,
+        Err(err) => return Box::new(::futures::future::err(err)),
+    };
+    Box::new(
+        stream::iter_ok(stale)
+            .map(move |id| {
+                let store = store.clone();
+                let saved = store.clone();
+                venues
+                    .get(id.as_str(), &VenueDetailsOptions::default())
+                    .join(venues.hours(id.as_str(), &HoursOptions::default()))
+                    .and_then(move |(details, hours)| {
+                        let venue = details.response.venue;
+                        saved.save_details(&venue, Utc::now())?;
+                        saved.save_hours(&venue.id, &hours.response)
+                    })
+                    .then(move |result| {
+                        store.attempted(&id, Utc::now())?;
+                        if let Err(ref err) = result {
+                            warn!("failed to refresh venue {}: {}", id, err);
+                        }
+                        Ok::<_, ::Error>(result.is_ok())
+                    })
+            })
+            .buffer_unordered(concurrency.max(1))
+            .fold(Refreshed::default(), |mut outcome, refreshed| {
+                if refreshed {
+                    outcome.refreshed += 1;
+                } else {
+                    outcome.failed += 1;
+                }
+                Ok::<_, ::Error>(outcome)
+            }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tokio_core::reactor::Core;
+
+    use {Client, Credentials};
+    use transport::MemoryTransport;
+
+    fn venue(json: &str) -> Venue {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn saves_and_queries_venues() {
+        let store = Store::in_memory().unwrap();
+        let now = Utc::now();
+        store
+            .save_venue(
+                &venue(
+                    r#"{"id": "1", "name": "Cafe", "categories": [],
+                        "location": {"lat": 37.5, "lng": 126.9,
+                                     "country": "KR"}}"#,
+                ),
+                now,
+            )
+            .unwrap();
+        store
+            .save_details(
+                &venue(
+                    r#"{"id": "2", "name": "Bakery", "categories": [],
+                        "timeZone": "Asia/Seoul",
+                        "location": {"lat": 37.5, "lng": 126.9,
+                                     "country": "KR"}}"#,
+                ),
+                now,
+            )
+            .unwrap();
+        // a later, less complete record of a venue keeps stored details
+        store
+            .save_venue(
+                &venue(
+                    r#"{"id": "2", "name": "Bakery", "categories": [],
+                        "location": {"lat": 37.5, "lng": 126.9,
+                                     "country": "KR"}}"#,
+                ),
+                now,
+            )
+            .unwrap();
+        assert_eq!(
+            store.venue("2").unwrap().unwrap().time_zone,
+            Some("Asia/Seoul".into())
+        );
+        assert_eq!(store.search("bak").unwrap().len(), 1);
+        assert_eq!(
+            store.stale(now - Duration::hours(1)).unwrap(),
+            vec!["1".to_string()]
+        );
+        assert!(store.last_seen("2").unwrap().unwrap().1.is_some())
+    }
+
+    #[test]
+    fn saves_hours() {
+        let store = Store::in_memory().unwrap();
+        store
+            .save_venue(
+                &venue(
+                    r#"{"id": "1", "name": "Cafe", "categories": [],
+                        "location": {"lat": 37.5, "lng": 126.9,
+                                     "country": "KR"}}"#,
+                ),
+                Utc::now(),
+            )
+            .unwrap();
+        let hours = serde_json::from_str::<VenueHoursResponse>(
+            r#"{"hours": {"timeframes": [
+                  {"days": [1, 2], "open": [{"start": "0800", "end": "1700"}]}
+                ]},
+                "popular": {"timeframes": []}}"#,
+        ).unwrap();
+        store.save_hours("1", &hours).unwrap();
+        // saving the venue again keeps its hours
+        store
+            .save_venue(&store.venue("1").unwrap().unwrap(), Utc::now())
+            .unwrap();
+        let stored = store.hours("1").unwrap().unwrap();
+        assert_eq!(stored.hours.timeframes.len(), 2);
+        assert_eq!(stored.hours.timeframes[1].days, vec![2]);
+        assert_eq!(stored.hours.timeframes[1].open[0].end, "1700")
+    }
+
+    fn details(tip: &str, photo: &str) -> String {
+        format!(
+            r#"{{"meta": {{"code": 200, "requestId": "1"}},
+                 "response": {{"venue": {{
+                   "id": "1", "name": "Cafe", "categories": [],
+                   "location": {{"lat": 37.5, "lng": 126.9, "country": "KR"}},
+                   "photos": {{"count": 1, "groups": [{{
+                     "type": "venue", "name": "Venue photos", "items": [{{
+                       "id": "{photo}", "createdAt": 1, "prefix": "p",
+                       "suffix": ".jpg", "width": 10, "height": 10,
+                       "visibility": "public"}}]}}]}},
+                   "tips": {{"count": 1, "groups": [{{
+                     "type": "others", "name": "All tips", "items": [{{
+                       "id": "{tip}", "text": "{tip}",
+                       "canonicalUrl": "https://foursquare.com/item/{tip}"
+                     }}]}}]}}}}}}}}"#,
+            tip = tip,
+            photo = photo
+        )
+    }
+
+    #[test]
+    fn refreshes_tips_and_photos() {
+        let store = Rc::new(Store::in_memory().unwrap());
+        let mut core = Core::new().unwrap();
+        let transport = MemoryTransport::new();
+        let fs = Client::with_transport(
+            "20170801",
+            Credentials::user("token"),
+            transport.clone(),
+        );
+        let mut refreshed = |details: String| {
+            // later routes take precedence over the details' path prefix
+            transport.respond("/v2/venues/1", 200, details);
+            transport.respond(
+                "/v2/venues/1/hours",
+                200,
+                r#"{"meta": {"code": 200, "requestId": "1"},
+                    "response": {"hours": {"timeframes": []},
+                                 "popular": {"timeframes": []}}}"#,
+            );
+            core.run(refresh(
+                store.clone(),
+                fs.venues(),
+                Utc::now() + Duration::hours(1),
+                1,
+            )).unwrap()
+                .refreshed
+        };
+
+        store
+            .save_venue(
+                &venue(
+                    r#"{"id": "1", "name": "Cafe", "categories": [],
+                        "location": {"lat": 37.5, "lng": 126.9,
+                                     "country": "KR"}}"#,
+                ),
+                Utc::now(),
+            )
+            .unwrap();
+        assert_eq!(refreshed(details("old tip", "old photo")), 1);
+        assert_eq!(store.tips("1").unwrap()[0].text, "old tip");
+
+        assert_eq!(refreshed(details("new tip", "new photo")), 1);
+        let tips = store.tips("1").unwrap();
+        assert_eq!(tips.len(), 1);
+        assert_eq!(tips[0].id, "new tip");
+        let photos = store
+            .conn
+            .borrow()
+            .query_row(
+                "SELECT group_concat(id) FROM photos WHERE venue_id = '1'",
+                &[],
+                |row| row.get::<_, String>(0),
+            )
+            .unwrap();
+        assert_eq!(photos, "new photo")
+    }
+
+    #[test]
+    fn refreshes_past_failures() {
+        let store = Rc::new(Store::in_memory().unwrap());
+        let mut core = Core::new().unwrap();
+        let transport = MemoryTransport::new();
+        let summary = |id: &str| {
+            format!(
+                r#"{{"id": "{}", "name": "Cafe", "categories": [],
+                     "location": {{"lat": 37.5, "lng": 126.9,
+                                   "country": "KR"}}}}"#,
+                id
+            )
+        };
+        for id in &["b", "a", "c"] {
+            store.save_venue(&venue(&summary(id)), Utc::now()).unwrap();
+        }
+        // b is unrouted, so its details fail with a 404
+        for id in &["a", "c"] {
+            transport.respond(
+                format!("/v2/venues/{}", id),
+                200,
+                format!(
+                    r#"{{"meta": {{"code": 200, "requestId": "1"}},
+                         "response": {{"venue": {}}}}}"#,
+                    summary(id)
+                ),
+            );
+            transport.respond(
+                format!("/v2/venues/{}/hours", id),
+                200,
+                r#"{"meta": {"code": 200, "requestId": "1"},
+                    "response": {"hours": {"timeframes": []},
+                                 "popular": {"timeframes": []}}}"#,
+            );
+        }
+        let fs = Client::with_transport(
+            "20170801",
+            Credentials::user("token"),
+            transport,
+        );
+        let refreshed = core.run(refresh(
+            store.clone(),
+            fs.venues(),
+            Utc::now() + Duration::hours(1),
+            2,
+        )).unwrap();
+        assert_eq!(
+            refreshed,
+            Refreshed {
+                refreshed: 2,
+                failed: 1,
+            }
+        );
+
+        // the failed venue is retried after venues not yet attempted
+        store.save_venue(&venue(&summary("d")), Utc::now()).unwrap();
+        assert_eq!(
+            store.stale(Utc::now() - Duration::hours(1)).unwrap(),
+            vec!["d".to_string(), "b".to_string()]
+        )
+    }
+}
@@ -0,0 +1,69 @@
+//! Helpers shared by the tests of the `blocking` and `asynchronous`
+//! clients
+
+use tokio_core::reactor::Handle;
+
+use {Client, Credentials};
+use transport::MemoryTransport;
+
+/// A successful venue hours response with no timeframes
+pub const HOURS: &str = r#"{"meta": {"code": 200, "requestId": "1"},
+    "response": {"hours": {"timeframes": []},
+                 "popular": {"timeframes": []}}}"#;
+
+/// Build a client serving `HOURS` for venue 1, for the background runtime
+/// of the `blocking` and `asynchronous` clients
+pub fn memory_client(_: &Handle) -> Client {
+    let transport = MemoryTransport::new();
+    transport.respond("/v2/venues/1/hours", 200, HOURS);
+    Client::with_transport("20170801", Credentials::user("token"), transport)
+}
+
+/// Poll a `std::future::Future` to completion on the current thread,
+/// parking it until woken
+#[cfg(feature = "std-future")]
+pub fn block_on<F>(mut future: F) -> F::Output
+where
+    F: ::std::future::Future,
+{
+    use std::mem::ManuallyDrop;
+    use std::pin::Pin;
+    use std::sync::Arc;
+    use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+    use std::thread::{self, Thread};
+
+    // wakers hold an `Arc<Thread>`, unparking it when woken
+    unsafe fn clone(data: *const ()) -> RawWaker {
+        // borrow the waker's reference rather than consuming it
+        let thread = ManuallyDrop::new(Arc::from_raw(data as *const Thread));
+        let cloned = Arc::clone(&thread);
+        RawWaker::new(Arc::into_raw(cloned) as *const (), &VTABLE)
+    }
+    unsafe fn wake(data: *const ()) {
+        Arc::from_raw(data as *const Thread).unpark()
+    }
+    unsafe fn wake_by_ref(data: *const ()) {
+        (*(data as *const Thread)).unpark()
+    }
+    unsafe fn drop(data: *const ()) {
+        Arc::from_raw(data as *const Thread);
+    }
+    static VTABLE: RawWakerVTable =
+        RawWakerVTable::new(clone, wake, wake_by_ref, drop);
+
+    let thread = Arc::new(thread::current());
+    let waker = unsafe {
+        Waker::from_raw(
+            RawWaker::new(Arc::into_raw(thread) as *const (), &VTABLE),
+        )
+    };
+    let mut cx = Context::from_waker(&waker);
+    // the future isn't moved again once pinned
+    let mut future = unsafe { Pin::new_unchecked(&mut future) };
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            _ => thread::park(),
+        }
+    }
+}
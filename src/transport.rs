@@ -0,0 +1,292 @@
+//! Pluggable HTTP transports
+//!
+//! A `Client` sends requests through a `Transport`, which need not be
+//! hyper based. Implement `Transport` to route api traffic through another
+//! HTTP stack, or use a `MemoryTransport` to serve canned responses in
+//! tests.
+//!
+//! ```
+//! extern crate foursquare;
+//! extern crate futures;
+//!
+//! use futures::Future;
+//! use foursquare::{Client, Credentials};
+//! use foursquare::transport::MemoryTransport;
+//! use foursquare::venue::SearchOptions;
+//!
+//! fn main() {
+//!   let transport = MemoryTransport::new();
+//!   transport.respond(
+//!     "/v2/venues/search",
+//!     200,
+//!     r#"{"meta": {"code": 200, "requestId": "1"},
+//!         "response": {"venues": []}}"#
+//!   );
+//!   let fs = Client::with_transport(
+//!     "YYYYMMDD",
+//!     Credentials::client("client_id", "client_secret"),
+//!     transport.clone()
+//!   );
+//!   let response = fs.venues()
+//!     .search(&SearchOptions::builder().near("Brooklyn, NY").build().unwrap())
+//!     .wait()
+//!     .unwrap();
+//!   assert!(response.response.venues.is_empty());
+//!   assert_eq!(transport.requests().len(), 1);
+//! }
+//! ```
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures::{Future as StdFuture, Stream};
+use futures::future;
+use hyper::{self, Client as HyperClient};
+use hyper::client::Connect;
+use url::Url;
+
+use {Error, Future};
+
+/// HTTP request methods
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// An HTTP request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: Method,
+    pub url: Url,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+/// An HTTP response with a fully read body
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Return true if the status is in the 2xx range
+    pub fn is_success(&self) -> bool {
+        self.status >= 200 && self.status < 300
+    }
+
+    /// Return the value of the first header with the provided name, ignoring
+    /// case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|&&(ref n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, ref value)| value.as_str())
+    }
+}
+
+/// A means of sending HTTP requests
+pub trait Transport {
+    /// Send a request, resolving to its response. Non-2xx responses should
+    /// resolve successfully; errors are reserved for failures to exchange a
+    /// request and response
+    fn send(&self, request: Request) -> Future<Response>;
+}
+
+/// A transport backed by a `hyper::Client`
+#[derive(Debug, Clone)]
+pub struct HyperTransport<C>
+where
+    C: Clone + Connect,
+{
+    http: HyperClient<C>,
+}
+
+impl<C> HyperTransport<C>
+where
+    C: Clone + Connect,
+{
+    /// Return a new transport sending requests with the provided client
+    pub fn new(http: HyperClient<C>) -> Self {
+        Self { http }
+    }
+}
+
+impl<C> From<HyperClient<C>> for HyperTransport<C>
+where
+    C: Clone + Connect,
+{
+    fn from(http: HyperClient<C>) -> Self {
+        Self::new(http)
+    }
+}
+
+impl<C> Transport for HyperTransport<C>
+where
+    C: Clone + Connect,
+{
+    fn send(&self, request: Request) -> Future<Response> {
+        let uri = match request.url.as_str().parse() {
+            Ok(uri) => uri,
+            Err(err) => return Box::new(future::err(Error::from(err))),
+        };
+        let method = match request.method {
+            Method::Get => hyper::Method::Get,
+            Method::Post => hyper::Method::Post,
+        };
+        let mut req = hyper::Request::new(method, uri);
+        for (name, value) in request.headers {
+            req.headers_mut().append_raw(name, value);
+        }
+        if let Some(body) = request.body {
+            req.set_body(body)
+        }
+        Box::new(self.http.request(req).map_err(Error::from).and_then(
+            |response| {
+                let status = response.status().as_u16();
+                let headers = response
+                    .headers()
+                    .iter()
+                    .map(|header| {
+                        (header.name().to_owned(), header.value_string())
+                    })
+                    .collect();
+                response.body().concat2().map_err(Error::from).map(
+                    move |body| {
+                        Response {
+                            status,
+                            headers,
+                            body: body.to_vec(),
+                        }
+                    },
+                )
+            },
+        ))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Routes {
+    responses: Vec<(String, Response)>,
+    requests: Vec<Request>,
+}
+
+/// A transport serving canned responses from memory, for use in tests.
+///
+/// Clones share their responses and recorded requests, so a clone may be
+/// kept to inspect requests made by a `Client`
+#[derive(Debug, Clone, Default)]
+pub struct MemoryTransport {
+    routes: Rc<RefCell<Routes>>,
+}
+
+impl MemoryTransport {
+    /// Return a new transport with no responses
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Serve a response with the provided status and json body to requests
+    /// whose url path starts with `path`. Routes added later take
+    /// precedence. Unrouted requests receive a 404 response
+    pub fn respond<P, B>(&self, path: P, status: u16, body: B)
+    where
+        P: Into<String>,
+        B: Into<String>,
+    {
+        self.routes.borrow_mut().responses.push((
+            path.into(),
+            Response {
+                status,
+                headers: vec![
+                    ("Content-Type".into(), "application/json".into()),
+                ],
+                body: body.into().into_bytes(),
+            },
+        ))
+    }
+
+    /// Return the requests sent so far, in the order they were sent
+    pub fn requests(&self) -> Vec<Request> {
+        self.routes.borrow().requests.clone()
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn send(&self, request: Request) -> Future<Response> {
+        let mut routes = self.routes.borrow_mut();
+        let response = routes
+            .responses
+            .iter()
+            .rev()
+            .find(|&&(ref path, _)| request.url.path().starts_with(path))
+            .map(|&(_, ref response)| response.clone())
+            .unwrap_or_else(|| {
+                Response {
+                    status: 404,
+                    headers: Vec::new(),
+                    body: br#"{"meta": {"code": 404, "requestId": "memory",
+                                "errorType": "not_found",
+                                "errorDetail": "no route"},
+                       "response": {}}"#
+                        .to_vec(),
+                }
+            });
+        routes.requests.push(request);
+        Box::new(future::ok(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {Client, Credentials, ErrorKind};
+    use venue::VenueDetailsOptions;
+
+    #[test]
+    fn memory_transport_serves_routes() {
+        let transport = MemoryTransport::new();
+        transport.respond(
+            "/v2/venues/1/hours",
+            200,
+            r#"{"meta": {"code": 200, "requestId": "1"},
+                "response": {"hours": {"timeframes": []},
+                             "popular": {"timeframes": []}}}"#,
+        );
+        let fs = Client::with_transport(
+            "20170801",
+            Credentials::user("token"),
+            transport.clone(),
+        );
+        let venues = fs.venues();
+        assert!(
+            venues
+                .hours("1", &Default::default())
+                .wait()
+                .unwrap()
+                .response
+                .hours
+                .timeframes
+                .is_empty()
+        );
+        match venues.get("2", &VenueDetailsOptions::default()).wait() {
+            Err(err) => {
+                match *err.kind() {
+                    ErrorKind::Fault { ref code, .. } => {
+                        assert_eq!(code.as_u16(), 404)
+                    }
+                    ref other => panic!("unexpected error {:?}", other),
+                }
+            }
+            _ => panic!("expected a fault"),
+        }
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, Method::Get);
+        assert!(requests[0].url.query_pairs().any(|(k, v)| {
+            k == "oauth_token" && v == "token"
+        }))
+    }
+}
@@ -0,0 +1,205 @@
+//! Venue representations borrowing their strings from a response body
+//!
+//! The owned representations in `foursquare::venue` allocate a `String` for
+//! every field of every venue. For large category, search and explore
+//! payloads, fetch a `Body` with `Venues::categories_body`,
+//! `Venues::search_body` or `Venues::explore_body` and parse it into the
+//! types here instead. Strings are borrowed from the body unless they
+//! contain json escapes, and fields not represented here are skipped
+//! without being allocated.
+//!
+//! ```no_run
+//! extern crate foursquare;
+//! extern crate tokio_core;
+//!
+//! use tokio_core::reactor::Core;
+//! use foursquare::{Client, Credentials};
+//! use foursquare::venue::SearchOptions;
+//! use foursquare::venue::borrowed::SearchResponse;
+//!
+//! fn main() {
+//!   let mut core = Core::new().expect("reactor fail");
+//!   let fs = Client::new(
+//!     "YYYYMMDD",
+//!     Credentials::client("client_id", "client_secret"),
+//!     &core.handle()
+//!   );
+//!   let body = core.run(
+//!     fs.venues().search_body(
+//!       &SearchOptions::builder().near("Brooklyn, NY").build().unwrap()
+//!     )
+//!   ).unwrap();
+//!   let response = body.parse::<SearchResponse>().unwrap();
+//!   for venue in response.response.venues {
+//!     println!("{}", venue.name)
+//!   }
+//! }
+//! ```
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Deserializer};
+
+/// serde only borrows `Cow` fields directly, not those nested in an
+/// `Option`, so optional strings are deserialized through this wrapper
+#[derive(Deserialize)]
+struct Borrowed<'a>(#[serde(borrow)] Cow<'a, str>);
+
+fn optional<'de, D>(deserializer: D) -> Result<Option<Cow<'de, str>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(Option::<Borrowed<'de>>::deserialize(deserializer)?.map(
+        |value| value.0,
+    ))
+}
+
+/// A venue. See `foursquare::venue::Venue` for field details
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Venue<'a> {
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(borrow)]
+    pub location: Location<'a>,
+    #[serde(borrow, default)]
+    pub categories: Vec<Category<'a>>,
+    #[serde(borrow, default, deserialize_with = "optional")]
+    pub url: Option<Cow<'a, str>>,
+    pub rating: Option<f32>,
+    pub verified: Option<bool>,
+}
+
+/// A venue's location. See `foursquare::venue::Location` for field details
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Location<'a> {
+    #[serde(borrow, default, deserialize_with = "optional")]
+    pub address: Option<Cow<'a, str>>,
+    #[serde(rename = "crossStreet", borrow, default,
+            deserialize_with = "optional")]
+    pub cross_street: Option<Cow<'a, str>>,
+    pub lat: f64,
+    pub lng: f64,
+    pub distance: Option<u32>,
+    #[serde(rename = "postalCode", borrow, default,
+            deserialize_with = "optional")]
+    pub postal_code: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "optional")]
+    pub cc: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "optional")]
+    pub city: Option<Cow<'a, str>>,
+    #[serde(borrow, default, deserialize_with = "optional")]
+    pub state: Option<Cow<'a, str>>,
+    #[serde(borrow)]
+    pub country: Cow<'a, str>,
+}
+
+/// Icon photo. See `foursquare::venue::Icon` for details
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Icon<'a> {
+    #[serde(borrow)]
+    pub prefix: Cow<'a, str>,
+    #[serde(borrow)]
+    pub suffix: Cow<'a, str>,
+}
+
+/// A venue category, along with its sub-categories when returned by the
+/// categories api
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Category<'a> {
+    #[serde(borrow)]
+    pub id: Cow<'a, str>,
+    #[serde(borrow)]
+    pub name: Cow<'a, str>,
+    #[serde(rename = "pluralName", borrow)]
+    pub plural_name: Cow<'a, str>,
+    #[serde(rename = "shortName", borrow)]
+    pub short_name: Cow<'a, str>,
+    #[serde(borrow)]
+    pub icon: Icon<'a>,
+    pub primary: Option<bool>,
+    #[serde(borrow, default)]
+    pub categories: Vec<Category<'a>>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct CategoriesResponse<'a> {
+    #[serde(borrow)]
+    pub categories: Vec<Category<'a>>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct SearchResponse<'a> {
+    #[serde(borrow)]
+    pub venues: Vec<Venue<'a>>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct VenueItem<'a> {
+    #[serde(borrow)]
+    pub venue: Venue<'a>,
+    #[serde(rename = "referralId", borrow)]
+    pub referral_id: Cow<'a, str>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct Group<'a> {
+    /// Not all group types are named
+    #[serde(borrow, default)]
+    pub name: Cow<'a, str>,
+    #[serde(rename = "type", borrow)]
+    pub group_type: Cow<'a, str>,
+    #[serde(borrow)]
+    pub items: Vec<VenueItem<'a>>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub struct ExploreResponse<'a> {
+    #[serde(rename = "totalResults")]
+    pub total_results: u64,
+    #[serde(borrow)]
+    pub groups: Vec<Group<'a>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Body;
+
+    #[test]
+    fn borrows_from_body() {
+        let body = Body::from(
+            br#"{"meta": {"code": 200, "requestId": "1"},
+                 "response": {"venues": [{
+                   "id": "1",
+                   "name": "Caf\u00e9 Grumpy",
+                   "url": "http://cafegrumpy.com",
+                   "location": {"address": "193 Meserole Ave",
+                                "lat": 40.72, "lng": -73.95,
+                                "country": "United States"},
+                   "categories": [{"id": "2", "name": "Coffee Shop",
+                                   "pluralName": "Coffee Shops",
+                                   "shortName": "Coffee Shop",
+                                   "icon": {"prefix": "p", "suffix": ".png"},
+                                   "primary": true}],
+                   "stats": {"checkinsCount": 1}
+                 }]}}"#
+                .to_vec(),
+        );
+        let response = body.parse::<SearchResponse>().unwrap();
+        let venue = &response.response.venues[0];
+        let borrowed = |value: &Cow<str>| match *value {
+            Cow::Borrowed(_) => true,
+            _ => false,
+        };
+        assert!(borrowed(&venue.id));
+        assert!(borrowed(venue.url.as_ref().unwrap()));
+        assert!(borrowed(venue.location.address.as_ref().unwrap()));
+        assert!(borrowed(&venue.categories[0].short_name));
+        // escaped strings can't be borrowed
+        assert_eq!(venue.name, "Café Grumpy");
+        assert!(!borrowed(&venue.name));
+        assert_eq!(venue.location.city, None)
+    }
+}
@@ -0,0 +1,488 @@
+//! Venue category taxonomy
+//!
+//! `Venues::categories` returns categories as a nested tree. A
+//! `CategoryTree` indexes that tree for constant time lookup by id and for
+//! navigating a category's ancestry.
+//!
+//! With the `category-snapshot` feature enabled, a snapshot of the taxonomy
+//! is embedded in the crate so that classification can run without first
+//! fetching categories from the api. The checked in snapshot holds only the
+//! top-level categories until it is regenerated with the
+//! `categories_snapshot` example, so fetch categories for now when
+//! classifying venues by their leaf categories.
+
+use std::collections::HashMap;
+
+#[cfg(feature = "category-snapshot")]
+use serde_json;
+
+use super::{CategoriesResponse, Category};
+
+/// The api version the embedded category snapshot was fetched with, written
+/// by the `categories_snapshot` example alongside the snapshot. Until the
+/// snapshot is regenerated this is the version the example requests, not
+/// one the checked in top-level categories were fetched with
+#[cfg(feature = "category-snapshot")]
+pub const SNAPSHOT_VERSION: &str =
+    include_str!("../../data/categories.version");
+
+/// Return the embedded category snapshot
+#[cfg(feature = "category-snapshot")]
+pub fn snapshot() -> CategoriesResponse {
+    serde_json::from_str(include_str!("../../data/categories.json"))
+        .expect("invalid category snapshot")
+}
+
+/// A difference between two versions of the category taxonomy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum CategoryChange {
+    /// A category present only in the newer taxonomy
+    Added {
+        id: String,
+        name: String,
+        parent: Option<String>,
+    },
+    /// A category present only in the older taxonomy
+    Removed { id: String, name: String },
+    /// A category whose name changed
+    Renamed { id: String, from: String, to: String },
+    /// A category that moved under a different parent
+    Reparented {
+        id: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Debug)]
+struct Node {
+    category: Category,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/// An index over the venue category taxonomy
+///
+/// Categories stored in the tree have their nested `categories` field
+/// removed. Use `children` and `descendants` to navigate the hierarchy.
+#[derive(Debug)]
+pub struct CategoryTree {
+    nodes: Vec<Node>,
+    ids: HashMap<String, usize>,
+    roots: Vec<usize>,
+}
+
+impl CategoryTree {
+    /// Return a new tree for a list of top-level categories
+    ///
+    /// When a category id appears more than once, the first occurrence is
+    /// kept and later ones are skipped along with their sub-categories
+    pub fn new(categories: Vec<Category>) -> Self {
+        let mut tree = Self {
+            nodes: Vec::new(),
+            ids: HashMap::new(),
+            roots: Vec::new(),
+        };
+        for category in categories {
+            if let Some(root) = tree.insert(category, None) {
+                tree.roots.push(root);
+            }
+        }
+        tree
+    }
+
+    fn insert(
+        &mut self,
+        mut category: Category,
+        parent: Option<usize>,
+    ) -> Option<usize> {
+        if self.ids.contains_key(&category.id) {
+            warn!(
+                "skipping duplicate category {} ({})",
+                category.id,
+                category.name
+            );
+            return None;
+        }
+        let children = category.categories.take().unwrap_or_default();
+        let index = self.nodes.len();
+        self.ids.insert(category.id.clone(), index);
+        self.nodes.push(Node {
+            category,
+            parent,
+            children: Vec::new(),
+        });
+        for child in children {
+            if let Some(child) = self.insert(child, Some(index)) {
+                self.nodes[index].children.push(child);
+            }
+        }
+        Some(index)
+    }
+
+    /// Return the number of categories in the tree
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Return true if the tree has no categories
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Return the category with the provided id
+    pub fn get(&self, id: &str) -> Option<&Category> {
+        self.ids.get(id).map(|&index| &self.nodes[index].category)
+    }
+
+    /// Return true if the tree contains a category with the provided id
+    pub fn contains(&self, id: &str) -> bool {
+        self.ids.contains_key(id)
+    }
+
+    /// Return the top-level categories
+    pub fn roots(&self) -> Vec<&Category> {
+        self.categories(&self.roots)
+    }
+
+    /// Return the immediate parent of a category, None for
+    /// top-level or unknown categories
+    pub fn parent(&self, id: &str) -> Option<&Category> {
+        self.ids
+            .get(id)
+            .and_then(|&index| self.nodes[index].parent)
+            .map(|parent| &self.nodes[parent].category)
+    }
+
+    /// Return the immediate children of a category
+    pub fn children(&self, id: &str) -> Vec<&Category> {
+        self.ids
+            .get(id)
+            .map(|&index| self.categories(&self.nodes[index].children))
+            .unwrap_or_default()
+    }
+
+    /// Return the ancestors of a category, starting with its parent and
+    /// ending with its top-level category
+    pub fn ancestors(&self, id: &str) -> Vec<&Category> {
+        let mut ancestors = Vec::new();
+        let mut current = self.ids.get(id).and_then(|&index| {
+            self.nodes[index].parent
+        });
+        while let Some(index) = current {
+            ancestors.push(&self.nodes[index].category);
+            current = self.nodes[index].parent;
+        }
+        ancestors
+    }
+
+    /// Return all categories nested under a category, depth first
+    pub fn descendants(&self, id: &str) -> Vec<&Category> {
+        let mut descendants = Vec::new();
+        if let Some(&index) = self.ids.get(id) {
+            let mut stack = self.nodes[index]
+                .children
+                .iter()
+                .rev()
+                .cloned()
+                .collect::<Vec<_>>();
+            while let Some(index) = stack.pop() {
+                descendants.push(&self.nodes[index].category);
+                stack.extend(self.nodes[index].children.iter().rev());
+            }
+        }
+        descendants
+    }
+
+    /// Return the top-level category a category belongs to. A top-level
+    /// category is its own root
+    pub fn root_of(&self, id: &str) -> Option<&Category> {
+        self.ancestors(id).pop().or_else(|| self.get(id))
+    }
+
+    /// Return all categories in the tree, depth first
+    pub fn flatten(&self) -> Vec<&Category> {
+        self.nodes.iter().map(|node| &node.category).collect()
+    }
+
+    /// Return a comma-delimited list of a category id and all of its
+    /// descendant ids, suitable for `SearchOptions` `category_id`
+    pub fn expand(&self, id: &str) -> Option<String> {
+        self.get(id).map(|category| {
+            ::std::iter::once(category)
+                .chain(self.descendants(id))
+                .map(|category| category.id.as_str())
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+    }
+
+    /// Return categories whose `name`, `plural_name` or `short_name`
+    /// approximately match a query, best matches first
+    ///
+    /// Matching is case insensitive. Exact matches rank above prefix
+    /// matches, which rank above substring matches, which rank above
+    /// names within a small edit distance of the query.
+    pub fn search(&self, query: &str) -> Vec<&Category> {
+        let query = query.trim().to_lowercase();
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let mut matches = self.nodes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, node)| {
+                let category = &node.category;
+                [&category.name, &category.plural_name, &category.short_name]
+                    .iter()
+                    .map(|name| score(&query, &name.to_lowercase()))
+                    .max()
+                    .and_then(|score| if score > 0 {
+                        Some((score, index))
+                    } else {
+                        None
+                    })
+            })
+            .collect::<Vec<_>>();
+        matches.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        matches
+            .into_iter()
+            .map(|(_, index)| &self.nodes[index].category)
+            .collect()
+    }
+
+    /// Return the changes between this taxonomy and a newer one, for
+    /// instance between the embedded snapshot and a live
+    /// `Venues::categories` result
+    pub fn diff(&self, newer: &CategoryTree) -> Vec<CategoryChange> {
+        let mut changes = Vec::new();
+        for node in &self.nodes {
+            let category = &node.category;
+            match newer.ids.get(&category.id) {
+                None => {
+                    changes.push(CategoryChange::Removed {
+                        id: category.id.clone(),
+                        name: category.name.clone(),
+                    })
+                }
+                Some(&index) => {
+                    let other = &newer.nodes[index];
+                    if other.category.name != category.name {
+                        changes.push(CategoryChange::Renamed {
+                            id: category.id.clone(),
+                            from: category.name.clone(),
+                            to: other.category.name.clone(),
+                        })
+                    }
+                    let (from, to) = (
+                        self.parent_id(node),
+                        newer.parent_id(other),
+                    );
+                    if from != to {
+                        changes.push(CategoryChange::Reparented {
+                            id: category.id.clone(),
+                            from,
+                            to,
+                        })
+                    }
+                }
+            }
+        }
+        for node in &newer.nodes {
+            if !self.contains(&node.category.id) {
+                changes.push(CategoryChange::Added {
+                    id: node.category.id.clone(),
+                    name: node.category.name.clone(),
+                    parent: newer.parent_id(node),
+                })
+            }
+        }
+        changes
+    }
+
+    fn parent_id(&self, node: &Node) -> Option<String> {
+        node.parent.map(|parent| self.nodes[parent].category.id.clone())
+    }
+
+    fn categories(&self, indexes: &[usize]) -> Vec<&Category> {
+        indexes
+            .iter()
+            .map(|&index| &self.nodes[index].category)
+            .collect()
+    }
+}
+
+impl From<CategoriesResponse> for CategoryTree {
+    fn from(response: CategoriesResponse) -> Self {
+        CategoryTree::new(response.categories)
+    }
+}
+
+/// rank how well a lowercase name matches a lowercase query, 0 being no match
+fn score(query: &str, name: &str) -> u8 {
+    if name == query {
+        4
+    } else if name.starts_with(query) {
+        3
+    } else if name.contains(query) {
+        2
+    } else if levenshtein(query, name) <=
+               ::std::cmp::max(1, query.chars().count() / 4)
+    {
+        1
+    } else {
+        0
+    }
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..b.len() + 1).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == *cb { 0 } else { 1 };
+            current.push(::std::cmp::min(
+                substitution,
+                ::std::cmp::min(previous[j + 1], current[j]) + 1,
+            ));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn tree() -> CategoryTree {
+        serde_json::from_str::<CategoriesResponse>(
+            r#"{"categories": [
+              {"id": "food", "name": "Food", "pluralName": "Food",
+               "shortName": "Food",
+               "icon": {"prefix": "p", "suffix": ".png"},
+               "categories": [
+                 {"id": "cafe", "name": "Café", "pluralName": "Cafés",
+                  "shortName": "Café",
+                  "icon": {"prefix": "p", "suffix": ".png"},
+                  "categories": [
+                    {"id": "coffee", "name": "Coffee Shop",
+                     "pluralName": "Coffee Shops", "shortName": "Coffee Shop",
+                     "icon": {"prefix": "p", "suffix": ".png"},
+                     "categories": []}
+                  ]},
+                 {"id": "bakery", "name": "Bakery", "pluralName": "Bakeries",
+                  "shortName": "Bakery",
+                  "icon": {"prefix": "p", "suffix": ".png"},
+                  "categories": []}
+               ]}
+            ]}"#,
+        ).unwrap()
+            .into()
+    }
+
+    fn ids(categories: Vec<&Category>) -> Vec<&str> {
+        categories.into_iter().map(|c| c.id.as_str()).collect()
+    }
+
+    #[test]
+    fn navigates_hierarchy() {
+        let tree = tree();
+        assert_eq!(tree.len(), 4);
+        assert_eq!(ids(tree.ancestors("coffee")), vec!["cafe", "food"]);
+        assert_eq!(
+            ids(tree.descendants("food")),
+            vec!["cafe", "coffee", "bakery"]
+        );
+        assert_eq!(tree.root_of("coffee").unwrap().id, "food");
+        assert_eq!(tree.root_of("food").unwrap().id, "food");
+        assert_eq!(tree.expand("cafe"), Some("cafe,coffee".into()))
+    }
+
+    #[test]
+    fn diffs_taxonomies() {
+        let mut newer = serde_json::to_value(tree().flatten()).unwrap();
+        // rename cafe, move coffee to the top level and drop bakery
+        newer[1]["name"] = "Coffeehouse".into();
+        let newer = CategoryTree::new(vec![
+            serde_json::from_value(newer[0].clone()).unwrap(),
+            serde_json::from_value(newer[1].clone()).unwrap(),
+            serde_json::from_value(newer[2].clone()).unwrap(),
+        ]);
+        assert_eq!(
+            tree().diff(&newer),
+            vec![
+                CategoryChange::Renamed {
+                    id: "cafe".into(),
+                    from: "Café".into(),
+                    to: "Coffeehouse".into(),
+                },
+                CategoryChange::Reparented {
+                    id: "cafe".into(),
+                    from: Some("food".into()),
+                    to: None,
+                },
+                CategoryChange::Reparented {
+                    id: "coffee".into(),
+                    from: Some("cafe".into()),
+                    to: None,
+                },
+                CategoryChange::Removed {
+                    id: "bakery".into(),
+                    name: "Bakery".into(),
+                },
+            ]
+        )
+    }
+
+    #[cfg(feature = "category-snapshot")]
+    #[test]
+    fn snapshot_parses() {
+        assert!(!CategoryTree::from(snapshot()).is_empty());
+        assert_eq!(SNAPSHOT_VERSION.len(), 8)
+    }
+
+    // data/categories.json currently holds only the top-level categories.
+    // Regenerate it with the categories_snapshot example, which requires api
+    // credentials, then remove this ignore
+    #[cfg(feature = "category-snapshot")]
+    #[test]
+    #[ignore]
+    fn snapshot_resolves_leaf_categories() {
+        let tree = CategoryTree::from(snapshot());
+        let coffee = tree.get("4bf58dd8d48988d1e0931735").expect(
+            "coffee shop missing from snapshot",
+        );
+        assert_eq!(coffee.name, "Coffee Shop");
+        assert_eq!(
+            tree.parent("4bf58dd8d48988d1e0931735").map(|parent| {
+                parent.id.as_str()
+            }),
+            Some("4d4b7105d754a06374d81259")
+        )
+    }
+
+    #[test]
+    fn searches_names() {
+        let tree = tree();
+        assert_eq!(ids(tree.search("coffee shops")), vec!["coffee"]);
+        assert_eq!(ids(tree.search("bakry")), vec!["bakery"]);
+        assert_eq!(ids(tree.search("caf")), vec!["cafe"]);
+        // 6 characters allow a single edit, though they span 8 bytes
+        assert!(tree.search("bäkéry").is_empty())
+    }
+
+    #[test]
+    fn keeps_first_duplicate() {
+        let mut categories = serde_json::to_value(tree().flatten()).unwrap();
+        categories[3]["id"] = "cafe".into();
+        let tree = CategoryTree::new(
+            serde_json::from_value(categories).unwrap(),
+        );
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.get("cafe").unwrap().name, "Café");
+        assert_eq!(ids(tree.roots()), vec!["food", "cafe", "coffee"])
+    }
+}
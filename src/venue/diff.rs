@@ -0,0 +1,322 @@
+//! Change detection between versions of a venue
+//!
+//! Compare a previously stored copy of a venue against a freshly fetched
+//! one with `Venue::diff`. Fields absent from the newer version, as is
+//! common for compact representations, are treated as unknown rather than
+//! removed.
+
+use std::fmt;
+
+use geo::Position;
+use super::{Contact, Coords, Hours, Venue};
+
+/// The smallest move, in meters, considered significant
+const SIGNIFICANT_DISTANCE: f64 = 100.0;
+
+/// The smallest change in rating considered significant
+const SIGNIFICANT_RATING: f32 = 0.5;
+
+/// A difference between two versions of a venue
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum VenueChange {
+    /// The venue's coordinates changed. Distance is in meters
+    Moved {
+        from: Coords,
+        to: Coords,
+        distance: f64,
+    },
+    Renamed { from: String, to: String },
+    /// The venue's category ids changed. Primary categories are listed
+    /// first
+    Recategorized { from: Vec<String>, to: Vec<String> },
+    HoursChanged {
+        from: Option<Hours>,
+        to: Option<Hours>,
+    },
+    /// The venue was marked as permanently closed
+    Closed,
+    /// A closed venue is no longer marked as closed
+    Reopened,
+    PriceTierChanged { from: Option<u16>, to: Option<u16> },
+    RatingChanged { from: Option<f32>, to: Option<f32> },
+    ContactChanged {
+        from: Option<Contact>,
+        to: Option<Contact>,
+    },
+}
+
+impl VenueChange {
+    /// Return true if this change is large enough to warrant attention.
+    /// Small moves and rating adjustments are typically noise
+    pub fn is_significant(&self) -> bool {
+        match *self {
+            VenueChange::Moved { distance, .. } => {
+                distance >= SIGNIFICANT_DISTANCE
+            }
+            VenueChange::RatingChanged { .. } => {
+                self.rating_delta().map_or(true, |delta| {
+                    delta.abs() >= SIGNIFICANT_RATING
+                })
+            }
+            _ => true,
+        }
+    }
+
+    /// Return the change in rating, if this is a change between two
+    /// ratings
+    pub fn rating_delta(&self) -> Option<f32> {
+        match *self {
+            VenueChange::RatingChanged {
+                from: Some(from),
+                to: Some(to),
+            } => Some(to - from),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for VenueChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn or_none<T: fmt::Display>(value: &Option<T>) -> String {
+            value.as_ref().map_or("none".into(), |v| v.to_string())
+        }
+        match *self {
+            VenueChange::Moved { distance, .. } => {
+                write!(f, "moved {:.0}m", distance)
+            }
+            VenueChange::Renamed { ref from, ref to } => {
+                write!(f, "renamed from {:?} to {:?}", from, to)
+            }
+            VenueChange::Recategorized { ref from, ref to } => {
+                write!(
+                    f,
+                    "categories changed from [{}] to [{}]",
+                    from.join(", "),
+                    to.join(", ")
+                )
+            }
+            VenueChange::HoursChanged { .. } => write!(f, "hours changed"),
+            VenueChange::Closed => write!(f, "closed"),
+            VenueChange::Reopened => write!(f, "reopened"),
+            VenueChange::PriceTierChanged { ref from, ref to } => {
+                write!(
+                    f,
+                    "price tier changed from {} to {}",
+                    or_none(from),
+                    or_none(to)
+                )
+            }
+            VenueChange::RatingChanged { ref from, ref to } => {
+                match self.rating_delta() {
+                    Some(delta) => write!(f, "rating changed by {:+.1}", delta),
+                    _ => {
+                        write!(
+                            f,
+                            "rating changed from {} to {}",
+                            or_none(from),
+                            or_none(to)
+                        )
+                    }
+                }
+            }
+            VenueChange::ContactChanged { .. } => {
+                write!(f, "contact changed")
+            }
+        }
+    }
+}
+
+/// The changes between two versions of a venue
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VenueDiff {
+    pub id: String,
+    /// The name of the newer version
+    pub name: String,
+    pub changes: Vec<VenueChange>,
+}
+
+impl VenueDiff {
+    /// Return true if the versions did not differ
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Return true if any change is significant
+    pub fn is_significant(&self) -> bool {
+        self.changes.iter().any(VenueChange::is_significant)
+    }
+
+    /// Return only the significant changes
+    pub fn significant(&self) -> Vec<&VenueChange> {
+        self.changes.iter().filter(|c| c.is_significant()).collect()
+    }
+}
+
+/// Summarizes changes on one line, e.g.
+/// `Cafe (4ab7e57cf964a5205f7b20e3): moved 250m, closed`
+impl fmt::Display for VenueDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}): ", self.name, self.id)?;
+        if self.changes.is_empty() {
+            return write!(f, "unchanged");
+        }
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+fn category_ids(venue: &Venue) -> Vec<String> {
+    let primary = venue.primary_category();
+    primary
+        .into_iter()
+        .chain(venue.categories.iter().filter(|c| {
+            primary.map_or(true, |p| p.id != c.id)
+        }))
+        .map(|c| c.id.clone())
+        .collect()
+}
+
+impl Venue {
+    /// Return the changes between this version of a venue and a newer one
+    pub fn diff(&self, newer: &Venue) -> VenueDiff {
+        let mut changes = Vec::new();
+        let (from, to) = (self.coords(), newer.coords());
+        if from != to {
+            changes.push(VenueChange::Moved {
+                from,
+                to,
+                distance: from.distance_to(&to),
+            })
+        }
+        if self.name != newer.name {
+            changes.push(VenueChange::Renamed {
+                from: self.name.clone(),
+                to: newer.name.clone(),
+            })
+        }
+        if !newer.categories.is_empty() {
+            let (from, to) = (category_ids(self), category_ids(newer));
+            if from != to {
+                changes.push(VenueChange::Recategorized { from, to })
+            }
+        }
+        // status, is_open and is_local_holiday depend on when a venue was
+        // fetched, so only the timeframes are compared
+        let timeframes = |venue: &Venue| {
+            venue.hours.as_ref().and_then(|hours| hours.timeframes.clone())
+        };
+        if timeframes(newer).is_some() && timeframes(self) != timeframes(newer) {
+            changes.push(VenueChange::HoursChanged {
+                from: self.hours.clone(),
+                to: newer.hours.clone(),
+            })
+        }
+        if let Some(closed) = newer.closed {
+            match (self.closed.unwrap_or(false), closed) {
+                (false, true) => changes.push(VenueChange::Closed),
+                (true, false) => changes.push(VenueChange::Reopened),
+                _ => (),
+            }
+        }
+        let tier = |venue: &Venue| venue.price.as_ref().map(|p| p.tier);
+        if newer.price.is_some() && tier(self) != tier(newer) {
+            changes.push(VenueChange::PriceTierChanged {
+                from: tier(self),
+                to: tier(newer),
+            })
+        }
+        if newer.rating.is_some() && self.rating != newer.rating {
+            changes.push(VenueChange::RatingChanged {
+                from: self.rating,
+                to: newer.rating,
+            })
+        }
+        if newer.contact.is_some() && self.contact != newer.contact {
+            changes.push(VenueChange::ContactChanged {
+                from: self.contact.clone(),
+                to: newer.contact.clone(),
+            })
+        }
+        VenueDiff {
+            id: newer.id.clone(),
+            name: newer.name.clone(),
+            changes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn venue(json: &str) -> Venue {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn diffs_venues() {
+        let before = venue(
+            r#"{"id": "1", "name": "Cafe", "categories": [],
+                "rating": 8.0, "price": {"tier": 1, "message": "Cheap",
+                                         "currency": "$"},
+                "location": {"lat": 37.5, "lng": 126.9, "country": "KR"}}"#,
+        );
+        let after = venue(
+            r#"{"id": "1", "name": "Cafe 2", "categories": [],
+                "rating": 8.2, "closed": true,
+                "location": {"lat": 37.501, "lng": 126.9, "country": "KR"}}"#,
+        );
+        let diff = before.diff(&after);
+        assert_eq!(diff.changes.len(), 4);
+        assert_eq!(diff.changes[1], VenueChange::Renamed {
+            from: "Cafe".into(),
+            to: "Cafe 2".into(),
+        });
+        // a ~111m move and a 0.2 rating bump, price unknown in newer
+        assert_eq!(
+            diff.to_string(),
+            "Cafe 2 (1): moved 111m, renamed from \"Cafe\" to \"Cafe 2\", \
+             closed, rating changed by +0.2"
+        );
+        assert_eq!(diff.significant().len(), 3);
+        assert!(before.diff(&before).is_empty());
+        // closure is unknown rather than reversed when absent
+        assert!(after.diff(&before).changes.iter().all(|change| {
+            *change != VenueChange::Reopened
+        }))
+    }
+
+    #[test]
+    fn ignores_time_dependent_hours() {
+        let hours = |status: &str, open: bool, days: &str| {
+            venue(&format!(
+                r#"{{"id": "1", "name": "Cafe", "categories": [],
+                    "location": {{"lat": 37.5, "lng": 126.9, "country": "KR"}},
+                    "hours": {{"status": "{}", "isOpen": {},
+                               "isLocalHoliday": false,
+                               "timeframes": [{{"days": "{}",
+                                                "open": [{{"renderedTime":
+                                                    "7:00 AM–7:00 PM"}}]}}]}}}}"#,
+                status,
+                open,
+                days
+            ))
+        };
+        let morning = hours("Open until 7:00 PM", true, "Mon–Sun");
+        let night = hours("Closed until 7:00 AM", false, "Mon–Sun");
+        assert!(morning.diff(&night).is_empty());
+        let weekdays = hours("Closed until 7:00 AM", false, "Mon–Fri");
+        let changes = morning.diff(&weekdays).changes;
+        assert_eq!(changes.len(), 1);
+        match changes[0] {
+            VenueChange::HoursChanged { .. } => (),
+            ref other => panic!("unexpected change {:?}", other),
+        }
+    }
+}
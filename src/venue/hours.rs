@@ -0,0 +1,265 @@
+//! Machine readable venue hours
+//!
+//! Foursquare represents hours as timeframes of days, numbered 1 (Monday)
+//! through 7 (Sunday), and open windows of `HHMM` strings where a `+` prefix
+//! indicates a time on the following day, e.g. a bar open `1700` to `+0200`.
+//!
+//! A `Schedule` resolves these against a venue's time zone so that you can
+//! ask whether a venue is open at a given instant without relying on the
+//! api's cached `Hours.is_open` value.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::Tz;
+
+use super::{TimeWindow, VenueHours};
+use {ErrorKind, Result};
+
+const MINUTES_PER_DAY: u32 = 24 * 60;
+const MINUTES_PER_WEEK: u32 = 7 * MINUTES_PER_DAY;
+
+/// A single open window, in minutes since the start of Monday
+#[derive(Debug, Clone, PartialEq)]
+struct Window {
+    start: u32,
+    end: u32,
+}
+
+/// A venue's weekly schedule, localized to the venue's time zone
+#[derive(Debug, Clone)]
+pub struct Schedule {
+    windows: Vec<Window>,
+    tz: Tz,
+}
+
+impl Schedule {
+    /// Return a new schedule for venue hours in the provided
+    /// [IANA time zone](https://www.iana.org/time-zones), typically the
+    /// venue's `time_zone` field
+    pub fn new<Z>(hours: &VenueHours, time_zone: Z) -> Result<Self>
+    where
+        Z: AsRef<str>,
+    {
+        let tz = time_zone.as_ref().parse::<Tz>().map_err(|_| {
+            ErrorKind::UnknownTimeZone(time_zone.as_ref().to_owned())
+        })?;
+        let mut windows = Vec::new();
+        for frame in &hours.timeframes {
+            for day in &frame.days {
+                if *day < 1 || *day > 7 {
+                    return Err(ErrorKind::InvalidHours(
+                        format!("unknown day {}", day),
+                    ).into());
+                }
+                let offset = (*day as u32 - 1) * MINUTES_PER_DAY;
+                for open in &frame.open {
+                    windows.push(window(offset, open)?);
+                }
+            }
+        }
+        windows.sort_by_key(|w| w.start);
+        Ok(Self { windows, tz })
+    }
+
+    /// The time zone this schedule is localized to
+    pub fn time_zone(&self) -> Tz {
+        self.tz
+    }
+
+    /// Return true if the venue is open at the provided instant
+    pub fn is_open_at<T>(&self, at: &DateTime<T>) -> bool
+    where
+        T: TimeZone,
+    {
+        self.containing(minute_of_week(&at.with_timezone(&self.tz)))
+            .is_some()
+    }
+
+    /// Return the next time the venue opens strictly after the provided
+    /// instant, or None if the venue has no open hours
+    pub fn next_opening<T>(&self, after: &DateTime<T>) -> Option<DateTime<Tz>>
+    where
+        T: TimeZone,
+    {
+        let local = after.with_timezone(&self.tz);
+        let now = minute_of_week(&local);
+        self.windows
+            .iter()
+            .map(|w| match (w.start + MINUTES_PER_WEEK - now) %
+                MINUTES_PER_WEEK {
+                0 => MINUTES_PER_WEEK,
+                delta => delta,
+            })
+            .min()
+            .and_then(|delta| self.localize(&local, delta))
+    }
+
+    /// Return the time the venue closes if it is open at the provided
+    /// instant, or None if it is closed
+    pub fn closing_time<T>(&self, at: &DateTime<T>) -> Option<DateTime<Tz>>
+    where
+        T: TimeZone,
+    {
+        let local = at.with_timezone(&self.tz);
+        let now = minute_of_week(&local);
+        self.containing(now).and_then(|w| {
+            let now = if now < w.start {
+                now + MINUTES_PER_WEEK
+            } else {
+                now
+            };
+            self.localize(&local, w.end - now)
+        })
+    }
+
+    /// the window containing a minute of the week, accounting for windows
+    /// that wrap from Sunday into Monday
+    fn containing(&self, minute: u32) -> Option<&Window> {
+        self.windows.iter().find(|w| {
+            (w.start <= minute && minute < w.end) ||
+                (w.start <= minute + MINUTES_PER_WEEK &&
+                     minute + MINUTES_PER_WEEK < w.end)
+        })
+    }
+
+    /// the local time `minutes` after `from`, truncated to the minute
+    fn localize(
+        &self,
+        from: &DateTime<Tz>,
+        minutes: u32,
+    ) -> Option<DateTime<Tz>> {
+        let naive = from.naive_local();
+        let naive = NaiveDateTime::new(
+            naive.date(),
+            naive.time().with_second(0).and_then(|t| t.with_nanosecond(0))?,
+        ) + Duration::minutes(minutes as i64);
+        self.tz.from_local_datetime(&naive).earliest().or_else(|| {
+            // local time fell in a daylight savings gap
+            self.tz
+                .from_local_datetime(&(naive + Duration::hours(1)))
+                .earliest()
+        })
+    }
+}
+
+impl VenueHours {
+    /// Return a `Schedule` for these hours in the provided time zone
+    pub fn schedule<Z>(&self, time_zone: Z) -> Result<Schedule>
+    where
+        Z: AsRef<str>,
+    {
+        Schedule::new(self, time_zone)
+    }
+}
+
+fn minute_of_week(at: &DateTime<Tz>) -> u32 {
+    at.weekday().num_days_from_monday() * MINUTES_PER_DAY + at.hour() * 60 +
+        at.minute()
+}
+
+fn window(offset: u32, open: &TimeWindow) -> Result<Window> {
+    let start = offset + minutes(&open.start)?;
+    let mut end = offset + minutes(&open.end)?;
+    if end <= start {
+        // overnight windows are not always marked as next day
+        end += MINUTES_PER_DAY;
+    }
+    Ok(Window { start, end })
+}
+
+/// parse `HHMM` or `+HHMM` as minutes since the start of the day
+fn minutes(time: &str) -> Result<u32> {
+    let (next_day, hhmm) = if time.starts_with('+') {
+        (true, &time[1..])
+    } else {
+        (false, time)
+    };
+    let invalid = || ErrorKind::InvalidHours(format!("invalid time {}", time));
+    if hhmm.len() != 4 || !hhmm.chars().all(|c| c.is_digit(10)) {
+        return Err(invalid().into());
+    }
+    let (hours, mins) = (
+        hhmm[..2].parse::<u32>().map_err(|_| invalid())?,
+        hhmm[2..].parse::<u32>().map_err(|_| invalid())?,
+    );
+    if hours > 24 || mins > 59 || (hours == 24 && mins > 0) {
+        return Err(invalid().into());
+    }
+    Ok(
+        hours * 60 + mins +
+            if next_day { MINUTES_PER_DAY } else { 0 },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use venue::Timeframe;
+
+    fn hours(days: Vec<u16>, start: &str, end: &str) -> VenueHours {
+        VenueHours {
+            timeframes: vec![
+                Timeframe {
+                    days,
+                    includes_today: None,
+                    open: vec![
+                        TimeWindow {
+                            start: start.into(),
+                            end: end.into(),
+                        },
+                    ],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn parses_next_day_times() {
+        assert_eq!(minutes("0930").unwrap(), 570);
+        assert_eq!(minutes("+0200").unwrap(), MINUTES_PER_DAY + 120);
+        assert!(minutes("25:00").is_err());
+        // well formed times out of range
+        assert_eq!(minutes("2400").unwrap(), MINUTES_PER_DAY);
+        assert!(minutes("2500").is_err());
+        assert!(minutes("+2500").is_err());
+        assert!(minutes("2401").is_err());
+        assert!(minutes("0960").is_err());
+    }
+
+    #[test]
+    fn open_past_midnight() {
+        // friday 5pm until 2am saturday
+        let schedule = hours(vec![5], "1700", "+0200")
+            .schedule("America/New_York")
+            .unwrap();
+        // 2017-09-02 is a saturday, 1am in new york is 5am utc
+        let late = Utc.ymd(2017, 9, 2).and_hms(5, 0, 0);
+        assert!(schedule.is_open_at(&late));
+        assert_eq!(
+            schedule.closing_time(&late).unwrap().naive_local(),
+            NaiveDateTime::parse_from_str("2017-09-02 02:00", "%Y-%m-%d %H:%M")
+                .unwrap()
+        );
+        let next = schedule
+            .next_opening(&Utc.ymd(2017, 9, 2).and_hms(12, 0, 0))
+            .unwrap();
+        assert_eq!(
+            next.naive_local(),
+            NaiveDateTime::parse_from_str("2017-09-08 17:00", "%Y-%m-%d %H:%M")
+                .unwrap()
+        )
+    }
+
+    #[test]
+    fn open_sunday_into_monday() {
+        let schedule = hours(vec![7], "2200", "+0100").schedule("UTC").unwrap();
+        // 2017-09-04 is a monday
+        assert!(schedule.is_open_at(&Utc.ymd(2017, 9, 4).and_hms(0, 30, 0)));
+        assert!(!schedule.is_open_at(&Utc.ymd(2017, 9, 4).and_hms(1, 30, 0)))
+    }
+
+    #[test]
+    fn unknown_time_zone() {
+        assert!(hours(vec![1], "0900", "1700").schedule("Mars/Base").is_err())
+    }
+}
@@ -4,21 +4,32 @@
 #![allow(unused_mut)]
 use std::fmt;
 
-use hyper::client::Connect;
+use chrono::{DateTime, Utc};
 use serde_urlencoded;
 
-use {Client, Future, Response};
-
-pub struct Venues<C>
-where
-    C: Connect + Clone,
-{
-    client: Client<C>,
+use {Body, Client, Future, Response};
+
+pub mod borrowed;
+pub mod categories;
+pub use self::categories::{CategoryChange, CategoryTree};
+pub mod diff;
+pub use self::diff::{VenueChange, VenueDiff};
+pub mod hours;
+pub use self::hours::Schedule;
+pub mod photos;
+pub use self::photos::{IconSize, PhotoSize};
+pub mod results;
+pub use self::results::{VenueResult, VenueResults};
+pub mod set;
+pub use self::set::VenueSet;
+
+pub struct Venues {
+    client: Client,
 }
 
-impl<C: Connect + Clone> Venues<C> {
+impl Venues {
     #[doc(hidden)]
-    pub(crate) fn new(client: Client<C>) -> Self {
+    pub(crate) fn new(client: Client) -> Self {
         Self { client }
     }
 
@@ -31,7 +42,13 @@ impl<C: Connect + Clone> Venues<C> {
         &self,
         options: &CategoriesOptions,
     ) -> Future<Response<CategoriesResponse>> {
-        self.client.get(format!(
+        self.client.parse(self.categories_body(options))
+    }
+
+    /// Get all venue categories, returning the buffered response body. Parse it into a
+    /// `borrowed::CategoriesResponse` to avoid allocating its strings
+    pub fn categories_body(&self, options: &CategoriesOptions) -> Future<Body> {
+        self.client.get_body(format!(
             "{host}/v2/venues/categories?={query}",
             host = self.client.host,
             query = serde_urlencoded::to_string(options).unwrap()
@@ -111,7 +128,13 @@ impl<C: Connect + Clone> Venues<C> {
         &self,
         options: &SearchOptions,
     ) -> Future<Response<SearchResponse>> {
-        self.client.get(format!(
+        self.client.parse(self.search_body(options))
+    }
+
+    /// Search for venues, returning the buffered response body. Parse it into a
+    /// `borrowed::SearchResponse` to avoid allocating its strings
+    pub fn search_body(&self, options: &SearchOptions) -> Future<Body> {
+        self.client.get_body(format!(
             "{host}/v2/venues/search?{query}",
             host = self.client.host,
             query = serde_urlencoded::to_string(options).unwrap()
@@ -159,7 +182,13 @@ impl<C: Connect + Clone> Venues<C> {
         &self,
         options: &ExploreOptions,
     ) -> Future<Response<ExploreResponse>> {
-        self.client.get(format!(
+        self.client.parse(self.explore_body(options))
+    }
+
+    /// Explore venues in a target geography, returning the buffered response body. Parse it into a
+    /// `borrowed::ExploreResponse` to avoid allocating its strings
+    pub fn explore_body(&self, options: &ExploreOptions) -> Future<Body> {
+        self.client.get_body(format!(
             "{host}/v2/venues/explore?{query}",
             host = self.client.host,
             query = serde_urlencoded::to_string(options).unwrap()
@@ -187,7 +216,7 @@ impl Default for Intent {
 /// Categories api options.
 ///
 /// Use Categories::builder() interface to construct these
-#[derive(Default, Debug, Deserialize, Serialize, Builder)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Builder)]
 #[builder(setter(into), default)]
 pub struct CategoriesOptions {
     /// [Internationalization](https://developer.foursquare.com/docs/api/configuration/internationalization)
@@ -203,7 +232,7 @@ impl CategoriesOptions {
 /// Search api options.
 ///
 /// Use SearchOptions::builder() interface to construct these
-#[derive(Default, Debug, Deserialize, Serialize, Builder)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Builder)]
 #[builder(setter(into), default)]
 pub struct SearchOptions {
     /// required unless near is provided. Latitude and longitude of the user’s location. Optional if using intent=global
@@ -253,7 +282,7 @@ impl SearchOptions {
     }
 }
 
-#[derive(Default, Debug, Deserialize, Serialize, Builder)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Builder)]
 #[builder(setter(into), default)]
 pub struct SuggestOptions {
     /// required Latitude and longitude of the user’s location. (Required for query searches)
@@ -293,7 +322,7 @@ impl SuggestOptions {
 /// Venue tips api options.
 ///
 /// Use TipsOptions::builder() interface to construct these
-#[derive(Default, Debug, Deserialize, Serialize, Builder)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Builder)]
 #[builder(setter(into), default)]
 pub struct TipsOptions {
     /// One of friends, recent, or popular.
@@ -315,7 +344,7 @@ impl TipsOptions {
 /// Venue hours api options.
 ///
 /// Use VenueDetailsOptions::builder() interface to construct these
-#[derive(Default, Debug, Deserialize, Serialize, Builder)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Builder)]
 #[builder(setter(into), default)]
 pub struct HoursOptions {
     /// [Internationalization](https://developer.foursquare.com/docs/api/configuration/internationalization)
@@ -331,7 +360,7 @@ impl HoursOptions {
 /// Venue details api options.
 ///
 /// Use VenueDetailsOptions::builder() interface to construct these
-#[derive(Default, Debug, Deserialize, Serialize, Builder)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Builder)]
 #[builder(setter(into), default)]
 pub struct VenueDetailsOptions {
     /// [Internationalization](https://developer.foursquare.com/docs/api/configuration/internationalization)
@@ -422,11 +451,40 @@ where
     }
 }
 
+/// (de)serialize optional timestamps represented as seconds since epoch
+mod epoch_seconds {
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(
+        ts: &Option<DateTime<Utc>>,
+        ser: S,
+    ) -> ::std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *ts {
+            Some(ref ts) => ser.serialize_some(&ts.timestamp()),
+            _ => ser.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(
+        de: D,
+    ) -> ::std::result::Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<i64>::deserialize(de)?.map(
+            |secs| Utc.timestamp(secs, 0),
+        ))
+    }
+}
 
 /// Recommedations api options.
 ///
 /// Use RecommendationsOptions::builder() interface to construct these
-#[derive(Default, Debug, Deserialize, Serialize, Builder)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Builder)]
 #[builder(setter(into), default)]
 pub struct RecommendationsOptions {
     /// required unless near is provided. Latitude and longitude of the user’s location.
@@ -505,7 +563,7 @@ impl RecommendationsOptions {
 /// Explore api options.
 ///
 /// Use ExploreOptions::builder() interface to construct these
-#[derive(Default, Debug, Deserialize, Serialize, Builder)]
+#[derive(Default, Debug, Clone, Deserialize, Serialize, Builder)]
 #[builder(setter(into), default)]
 pub struct ExploreOptions {
     /// required unless near is provided. Latitude and longitude of the user’s location.
@@ -567,13 +625,13 @@ impl ExploreOptions {
     }
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
 pub struct Coords {
     pub lat: f64,
     pub lng: f64,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Contact {
     pub phone: Option<String>,
     #[serde(rename = "formattedPhone")]
@@ -586,14 +644,14 @@ pub struct Contact {
     pub facebook_name: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Price {
     pub tier: u16,
     pub message: String,
     pub currency: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Location {
     pub address: Option<String>,
     #[serde(rename = "crossStreet")]
@@ -616,13 +674,15 @@ pub struct Location {
 /// Icon photo
 ///
 /// Pieces needed to construct category icons at various sizes. Combine prefix with a size (32, 44, 64, and 88 are available) and suffix, e.g. https://foursquare.com/img/categories/food/default_64.png. To get an image with a gray background, use bg_ before the size, e.g. https://foursquare.com/img/categories_v2/food/icecream_bg_32.png.
-#[derive(Debug, Deserialize, Serialize)]
+///
+/// Use `Icon::url` or `Icon::background_url` to construct these
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Icon {
     pub prefix: String,
     pub suffix: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Category {
     pub id: String,
     pub name: String,
@@ -635,13 +695,13 @@ pub struct Category {
     pub categories: Option<Vec<Category>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Menu {
     pub label: String,
     pub url: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Photos {
     pub count: u64,
     pub groups: Vec<Group<PhotoItem>>,
@@ -650,8 +710,8 @@ pub struct Photos {
 /// venue photo
 ///
 /// see [this doc](https://developer.foursquare.com/docs/api/photos/details)
-/// for photo url construction
-#[derive(Debug, Deserialize, Serialize)]
+/// for photo url construction or use `PhotoItem::url`
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PhotoItem {
     pub id: String,
     pub prefix: String,
@@ -662,7 +722,7 @@ pub struct PhotoItem {
     pub visibility: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct User {
     pub id: String,
     #[serde(rename = "firstName")]
@@ -675,20 +735,20 @@ pub struct User {
 /// user photo
 ///
 /// see [this doc](https://developer.foursquare.com/docs/api/photos/details)
-/// for photo url construction
-#[derive(Debug, Deserialize, Serialize)]
+/// for photo url construction or use `UserPhoto::url`
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct UserPhoto {
     pub prefix: String,
     pub suffix: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TimeWindow {
     pub start: String,
     pub end: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Timeframe {
     pub days: Vec<u16>,
     #[serde(rename = "includesToday")]
@@ -696,12 +756,12 @@ pub struct Timeframe {
     pub open: Vec<TimeWindow>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct VenueHours {
     pub timeframes: Vec<Timeframe>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct VenueHoursResponse {
     /// An array of timeframes of open hours.
     pub hours: VenueHours,
@@ -709,21 +769,212 @@ pub struct VenueHoursResponse {
     pub popular: VenueHours,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct CategoriesResponse {
     pub categories: Vec<Category>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// Human-readable hours, as embedded in venue representations
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Hours {
     pub status: Option<String>,
     #[serde(rename = "isOpen")]
     pub is_open: bool,
     #[serde(rename = "isLocalHoliday")]
     pub is_local_holiday: bool,
+    /// only present in details requests
+    pub timeframes: Option<Vec<RenderedTimeframe>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RenderedTime {
+    #[serde(rename = "renderedTime")]
+    pub rendered_time: String,
+}
+
+/// A timeframe as rendered for display, e.g. days of "Mon–Fri"
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct RenderedTimeframe {
+    pub days: String,
+    #[serde(rename = "includesToday")]
+    pub includes_today: Option<bool>,
+    pub open: Vec<RenderedTime>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Stats {
+    /// Total checkins ever here
+    #[serde(rename = "checkinsCount")]
+    pub checkins_count: Option<u64>,
+    /// Total users who have ever checked in here
+    #[serde(rename = "usersCount")]
+    pub users_count: Option<u64>,
+    /// Number of tips here
+    #[serde(rename = "tipCount")]
+    pub tip_count: Option<u64>,
+    /// Total visits here
+    #[serde(rename = "visitsCount")]
+    pub visits_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Checkin {
+    pub id: String,
+    #[serde(rename = "createdAt", default, with = "epoch_seconds")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "type")]
+    pub checkin_type: Option<String>,
+    /// Offset in minutes between when this check-in occurred and UTC
+    #[serde(rename = "timeZoneOffset")]
+    pub time_zone_offset: Option<i32>,
+    pub user: Option<User>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct HereNow {
+    pub count: u64,
+    pub summary: Option<String>,
+    /// only present for authenticated details requests
+    #[serde(default)]
+    pub groups: Vec<Group<Checkin>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct VenueTips {
+    pub count: u64,
+    #[serde(default)]
+    pub groups: Vec<Group<Tip>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Count {
+    pub count: u64,
+}
+
+/// A user created list of venues
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct List {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(rename = "type")]
+    pub list_type: Option<String>,
+    pub user: Option<User>,
+    pub editable: Option<bool>,
+    pub public: Option<bool>,
+    pub collaborative: Option<bool>,
+    pub url: Option<String>,
+    #[serde(rename = "canonicalUrl")]
+    pub canonical_url: Option<String>,
+    #[serde(rename = "createdAt", default, with = "epoch_seconds")]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(rename = "updatedAt", default, with = "epoch_seconds")]
+    pub updated_at: Option<DateTime<Utc>>,
+    pub photo: Option<PhotoItem>,
+    pub followers: Option<Count>,
+    #[serde(rename = "listItems")]
+    pub list_items: Option<Count>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Listed {
+    pub count: u64,
+    #[serde(default)]
+    pub groups: Vec<Group<List>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BeenHere {
+    pub count: u64,
+    #[serde(rename = "unconfirmedCount")]
+    pub unconfirmed_count: Option<u64>,
+    pub marked: Option<bool>,
+    #[serde(rename = "lastCheckinExpiredAt", default, with = "epoch_seconds")]
+    pub last_checkin_expired_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Special {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub special_type: String,
+    pub message: String,
+    pub description: Option<String>,
+    pub title: Option<String>,
+    pub icon: Option<String>,
+    pub state: Option<String>,
+    pub provider: Option<String>,
+    pub redemption: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Specials {
+    pub count: u64,
+    #[serde(default)]
+    pub items: Vec<Special>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Likes {
+    pub count: u64,
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub groups: Vec<Group<User>>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PageInfo {
+    pub description: Option<String>,
+    pub banner: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Page {
+    #[serde(rename = "pageInfo")]
+    pub page_info: Option<PageInfo>,
+    pub user: Option<User>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Reason {
+    pub summary: String,
+    #[serde(rename = "type")]
+    pub reason_type: String,
+    #[serde(rename = "reasonName")]
+    pub reason_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Reasons {
+    pub count: u64,
+    #[serde(default)]
+    pub items: Vec<Reason>,
+}
+
+/// Delivery provider icon
+///
+/// Combine prefix with one of the available sizes and name
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeliveryIcon {
+    pub prefix: String,
+    pub sizes: Vec<u16>,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeliveryProvider {
+    pub name: String,
+    pub icon: Option<DeliveryIcon>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Delivery {
+    pub id: String,
+    pub url: String,
+    pub provider: DeliveryProvider,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Tip {
     pub id: String,
     pub text: String,
@@ -732,18 +983,18 @@ pub struct Tip {
     pub photo: Option<PhotoItem>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Tips {
     pub count: u32,
     pub items: Vec<Tip>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct TipsResponse {
     pub tips: Tips,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Venue {
     /// A unique string identifier for this venue.
     pub id: String,
@@ -759,8 +1010,8 @@ pub struct Venue {
     /// Boolean indicating whether the owner of this business has claimed it and verified the information.
     /// Will be be None for suggest requests
     pub verified: Option<bool>,
-    // Contains checkinsCount (total checkins ever here), usersCount (total users who have ever checked in here), and tipCount (number of tips here).
-    // pub stats: Stats
+    /// Contains checkinsCount (total checkins ever here), usersCount (total users who have ever checked in here), and tipCount (number of tips here).
+    pub stats: Option<Stats>,
     /// URL of the venue’s website, typically provided by the venue manager.
     pub url: Option<String>,
     /// Contains the hours during the week that the venue is open along with any named hours segments in a human-readable format. For machine readable hours see venues/hours
@@ -772,16 +1023,54 @@ pub struct Venue {
     pub menu: Option<Menu>,
     /// An object containing the price tier from 1 (least pricey) - 4 (most pricey) and a message describing the price tier.
     pub price: Option<Price>,
-    // Information about who is here now. If present, there is always a count, the number of people here. If viewing details and there is a logged-in user, there is also a groups field with friends and others as types.
-    // pub hereNow: ???
-    // Seconds since epoch when the venue was created.
-    // pub createdAt: ???
+    /// Information about who is here now. If present, there is always a count, the number of people here. If viewing details and there is a logged-in user, there is also a groups field with friends and others as types.
+    #[serde(rename = "hereNow")]
+    pub here_now: Option<HereNow>,
+    /// Time the venue was created, deserialized from seconds since epoch.
+    /// only present in details requests
+    #[serde(rename = "createdAt", default, with = "epoch_seconds")]
+    pub created_at: Option<DateTime<Utc>>,
     /// A count and groups of photos for this venue. Group types are checkin and venue. Not all items will be present.
     /// Will typically not be present for search requests
     pub photos: Option<Photos>,
-    // Contains the total count of tips and groups with friends and others as groupTypes. Groups may change over time.
-    // pub tips: ??,
-    // ??
+    /// Contains the total count of tips and groups with friends and others as groupTypes. Groups may change over time.
+    /// only present in details requests
+    pub tips: Option<VenueTips>,
+    /// Groups of lists that contain this venue. Group types are created, edited, followed, friends and others.
+    /// only present in details requests
+    pub listed: Option<Listed>,
+    /// Contains the number of times the acting user has been here.
+    /// Absent for userless requests
+    #[serde(rename = "beenHere")]
+    pub been_here: Option<BeenHere>,
+    /// Specials available at this venue.
+    pub specials: Option<Specials>,
+    /// The count of users who have liked this venue, and groups containing any friends and others who have liked it.
+    /// only present in details requests
+    pub likes: Option<Likes>,
+    /// Indicates if the acting user has disliked this venue.
+    /// Absent for userless requests
+    pub dislike: Option<bool>,
+    /// Contains the hours during the week when people usually go to the venue.
+    /// only present in details requests
+    pub popular: Option<Hours>,
+    /// The photo foursquare considers the best representation of this venue.
+    /// only present in details requests
+    #[serde(rename = "bestPhoto")]
+    pub best_photo: Option<PhotoItem>,
+    /// A short URL for this venue, e.g. http://4sq.com/Ab123D
+    /// only present in details requests
+    #[serde(rename = "shortUrl")]
+    pub short_url: Option<String>,
+    /// The venue manager's internal identifier for this venue.
+    #[serde(rename = "storeId")]
+    pub store_id: Option<String>,
+    /// The branded page associated with this venue, if any.
+    pub page: Option<Page>,
+    /// Reasons this venue may be of interest to the acting user.
+    pub reasons: Option<Reasons>,
+    /// Third party delivery service available for this venue.
+    pub delivery: Option<Delivery>,
     #[serde(rename = "referralId")]
     pub referral_id: Option<String>,
     #[serde(rename = "hasPerk")]
@@ -800,38 +1089,54 @@ pub struct Venue {
     /// only present in details requests
     #[serde(rename = "canonicalUrl")]
     pub canonical_url: Option<String>,
+    /// Indicates the venue has permanently closed.
+    /// Typically only present when true
+    pub closed: Option<bool>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+impl Venue {
+    /// Return the venue's primary category, falling back on its first
+    /// category when none is marked primary
+    pub fn primary_category(&self) -> Option<&Category> {
+        self.categories
+            .iter()
+            .find(|c| c.primary.unwrap_or(false))
+            .or_else(|| self.categories.first())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SuggestResponse {
     pub minivenues: Vec<Venue>,
 }
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SearchResponse {
     pub venues: Vec<Venue>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct VenueItem {
     pub venue: Venue,
     #[serde(rename = "referralId")]
     pub referral_id: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Group<I> {
+    /// Not all group types are named, e.g. likes
+    #[serde(default)]
     pub name: String,
     #[serde(rename = "type")]
     pub group_type: String,
     pub count: Option<u64>,
     pub items: Vec<I>,
 }
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AttributeGroups {
     pub groups: Vec<Group<AttributeItem>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AttributeItem {
     #[serde(rename = "displayName")]
     pub display_name: String,
@@ -839,12 +1144,12 @@ pub struct AttributeItem {
     pub display_value: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Warning {
     pub text: String,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ExploreResponse {
     pub warning: Option<Warning>,
     /// If no radius was specified in the request, presents the radius that was used for the query (based upon the density of venues in the query area).
@@ -865,14 +1170,14 @@ pub struct ExploreResponse {
     pub groups: Vec<Group<VenueItem>>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct RecommendationsResponse {
     #[serde(rename = "normalizedQuery")]
     pub normalized_query: Option<String>,
     pub group: RecommendationsGroup,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct RecommendationsGroup {
     #[serde(rename = "totalResults")]
     pub total_results: u64,
@@ -880,7 +1185,7 @@ pub struct RecommendationsGroup {
     pub results: Vec<Recommendation>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Recommendation {
     #[serde(rename = "displayType")]
     pub display_type: String,
@@ -888,7 +1193,7 @@ pub struct Recommendation {
     pub photo: Option<PhotoItem>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct VenueResponse {
     pub venue: Venue,
 }
@@ -896,6 +1201,7 @@ pub struct VenueResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json;
     #[test]
     fn search_options_serialize() {
         assert_eq!(
@@ -906,6 +1212,34 @@ mod tests {
         )
     }
 
+    #[test]
+    fn venue_details_deserialize() {
+        let venue = serde_json::from_str::<Venue>(
+            r#"{
+              "id": "4b63f4c0f964a5209b982ae3",
+              "name": "Cafe",
+              "location": {
+                "lat": 37.5665, "lng": 126.978, "country": "South Korea"
+              },
+              "categories": [],
+              "stats": { "tipCount": 12 },
+              "hereNow": { "count": 0, "summary": "Nobody here" },
+              "createdAt": 1264841920,
+              "likes": {
+                "count": 2,
+                "groups": [{ "type": "others", "count": 2, "items": [] }],
+                "summary": "2 Likes"
+              },
+              "shortUrl": "http://4sq.com/8Ywt8O"
+            }"#,
+        ).unwrap();
+        assert_eq!(venue.stats.unwrap().tip_count, Some(12));
+        assert_eq!(venue.here_now.unwrap().count, 0);
+        assert_eq!(venue.created_at.unwrap().timestamp(), 1264841920);
+        assert_eq!(venue.likes.unwrap().groups[0].group_type, "others");
+        assert_eq!(venue.short_url, Some("http://4sq.com/8Ywt8O".into()))
+    }
+
     #[test]
     fn default_intent() {
         let default: Intent = Default::default();
@@ -0,0 +1,144 @@
+//! Photo and icon url construction
+//!
+//! The api represents images as a `prefix` and `suffix` which must be combined
+//! with a size to produce a usable url. See
+//! [this doc](https://developer.foursquare.com/docs/api/photos/details)
+//! for more information
+
+use std::fmt;
+
+use super::{DeliveryIcon, Icon, PhotoItem, UserPhoto};
+
+/// Size of a venue or user photo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PhotoSize {
+    /// The original photo as uploaded
+    Original,
+    /// Scaled so that neither width nor height exceeds this many pixels
+    Cap(u16),
+    /// Scaled to this width in pixels, preserving aspect ratio
+    Width(u16),
+    /// Cropped and scaled to this exact width and height in pixels
+    Exact(u16, u16),
+}
+
+impl fmt::Display for PhotoSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PhotoSize::Original => write!(f, "original"),
+            PhotoSize::Cap(max) => write!(f, "cap{}", max),
+            PhotoSize::Width(width) => write!(f, "width{}", width),
+            PhotoSize::Exact(width, height) => {
+                write!(f, "{}x{}", width, height)
+            }
+        }
+    }
+}
+
+/// Size of a category icon
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IconSize {
+    /// 32x32 pixels
+    Small,
+    /// 44x44 pixels
+    Medium,
+    /// 64x64 pixels
+    Large,
+    /// 88x88 pixels
+    ExtraLarge,
+}
+
+impl fmt::Display for IconSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match *self {
+                IconSize::Small => "32",
+                IconSize::Medium => "44",
+                IconSize::Large => "64",
+                IconSize::ExtraLarge => "88",
+            }
+        )
+    }
+}
+
+impl PhotoItem {
+    /// Return the url of this photo at the provided size
+    pub fn url(&self, size: PhotoSize) -> String {
+        format!("{}{}{}", self.prefix, size, self.suffix)
+    }
+}
+
+impl UserPhoto {
+    /// Return the url of this photo at the provided size
+    pub fn url(&self, size: PhotoSize) -> String {
+        format!("{}{}{}", self.prefix, size, self.suffix)
+    }
+}
+
+impl Icon {
+    /// Return the url of this icon at the provided size
+    pub fn url(&self, size: IconSize) -> String {
+        format!("{}{}{}", self.prefix, size, self.suffix)
+    }
+
+    /// Return the url of this icon at the provided size on a grey background
+    pub fn background_url(&self, size: IconSize) -> String {
+        format!("{}bg_{}{}", self.prefix, size, self.suffix)
+    }
+}
+
+impl DeliveryIcon {
+    /// Return the url of this icon at the provided size, or None if
+    /// the size is not one of the available `sizes`
+    pub fn url(&self, size: u16) -> Option<String> {
+        if self.sizes.contains(&size) {
+            Some(format!("{}{}{}", self.prefix, size, self.name))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn photo_urls() {
+        let photo = UserPhoto {
+            prefix: "https://igx.4sqi.net/img/user/".into(),
+            suffix: "/HBVX4T2WQOGNFOQH.jpg".into(),
+        };
+        assert_eq!(
+            photo.url(PhotoSize::Exact(300, 500)),
+            "https://igx.4sqi.net/img/user/300x500/HBVX4T2WQOGNFOQH.jpg"
+        );
+        assert_eq!(
+            photo.url(PhotoSize::Cap(300)),
+            "https://igx.4sqi.net/img/user/cap300/HBVX4T2WQOGNFOQH.jpg"
+        );
+        assert_eq!(
+            photo.url(PhotoSize::Original),
+            "https://igx.4sqi.net/img/user/original/HBVX4T2WQOGNFOQH.jpg"
+        )
+    }
+
+    #[test]
+    fn icon_urls() {
+        let icon = Icon {
+            prefix: "https://ss3.4sqi.net/img/categories_v2/food/icecream_"
+                .into(),
+            suffix: ".png".into(),
+        };
+        assert_eq!(
+            icon.url(IconSize::Large),
+            "https://ss3.4sqi.net/img/categories_v2/food/icecream_64.png"
+        );
+        assert_eq!(
+            icon.background_url(IconSize::Small),
+            "https://ss3.4sqi.net/img/categories_v2/food/icecream_bg_32.png"
+        )
+    }
+}
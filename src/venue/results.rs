@@ -0,0 +1,127 @@
+//! Uniform access to venues across response types
+//!
+//! Each venue operation nests its venues differently. `VenueResults`
+//! provides a single interface for iterating over them.
+
+use super::{ExploreResponse, PhotoItem, RecommendationsResponse,
+            SearchResponse, SuggestResponse, Venue};
+
+/// A venue along with the context it was returned in
+#[derive(Debug, Clone, Copy)]
+pub struct VenueResult<'a> {
+    pub venue: &'a Venue,
+    /// Referral id, used to attribute subsequent requests to this result
+    pub referral_id: Option<&'a str>,
+    /// Name of the group the venue was returned in, e.g. "recommended"
+    pub group: Option<&'a str>,
+    /// Photo returned alongside the venue
+    pub photo: Option<&'a PhotoItem>,
+}
+
+impl<'a> VenueResult<'a> {
+    fn new(venue: &'a Venue) -> Self {
+        VenueResult {
+            venue,
+            referral_id: venue.referral_id.as_ref().map(String::as_str),
+            group: None,
+            photo: None,
+        }
+    }
+}
+
+/// Types containing a list of venues
+pub trait VenueResults {
+    /// Return an iterator over venues and the context they were returned in
+    fn results<'a>(&'a self) -> Box<Iterator<Item = VenueResult<'a>> + 'a>;
+
+    /// Return an iterator over venues
+    fn venues<'a>(&'a self) -> Box<Iterator<Item = &'a Venue> + 'a> {
+        Box::new(self.results().map(|result| result.venue))
+    }
+}
+
+impl VenueResults for [Venue] {
+    fn results<'a>(&'a self) -> Box<Iterator<Item = VenueResult<'a>> + 'a> {
+        Box::new(self.iter().map(VenueResult::new))
+    }
+}
+
+impl VenueResults for Vec<Venue> {
+    fn results<'a>(&'a self) -> Box<Iterator<Item = VenueResult<'a>> + 'a> {
+        self.as_slice().results()
+    }
+}
+
+impl VenueResults for SearchResponse {
+    fn results<'a>(&'a self) -> Box<Iterator<Item = VenueResult<'a>> + 'a> {
+        self.venues.results()
+    }
+}
+
+impl VenueResults for SuggestResponse {
+    fn results<'a>(&'a self) -> Box<Iterator<Item = VenueResult<'a>> + 'a> {
+        self.minivenues.results()
+    }
+}
+
+impl VenueResults for ExploreResponse {
+    fn results<'a>(&'a self) -> Box<Iterator<Item = VenueResult<'a>> + 'a> {
+        Box::new(self.groups.iter().flat_map(|group| {
+            group.items.iter().map(move |item| {
+                VenueResult {
+                    referral_id: Some(item.referral_id.as_str()),
+                    group: Some(group.name.as_str()),
+                    ..VenueResult::new(&item.venue)
+                }
+            })
+        }))
+    }
+}
+
+impl VenueResults for RecommendationsResponse {
+    fn results<'a>(&'a self) -> Box<Iterator<Item = VenueResult<'a>> + 'a> {
+        Box::new(self.group.results.iter().map(|recommendation| {
+            VenueResult {
+                photo: recommendation.photo.as_ref(),
+                ..VenueResult::new(&recommendation.venue)
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    #[test]
+    fn explore_results() {
+        let response = serde_json::from_str::<ExploreResponse>(
+            r#"{
+              "headerLocation": "SoHo",
+              "headerFullLocation": "SoHo, New York",
+              "headerLocationGranularity": "neighborhood",
+              "totalResults": 1,
+              "groups": [{
+                "type": "Recommended Places", "name": "recommended",
+                "items": [{
+                  "referralId": "e-0-1-0",
+                  "venue": {
+                    "id": "1", "name": "Cafe", "categories": [],
+                    "location": {"lat": 40.72, "lng": -74.0, "country": "US"}
+                  }
+                }]
+              }]
+            }"#,
+        ).unwrap();
+        let results = response.results().collect::<Vec<_>>();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].venue.id, "1");
+        assert_eq!(results[0].referral_id, Some("e-0-1-0"));
+        assert_eq!(results[0].group, Some("recommended"));
+        assert_eq!(
+            response.venues().map(|v| v.name.as_str()).collect::<Vec<_>>(),
+            vec!["Cafe"]
+        )
+    }
+}
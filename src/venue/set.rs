@@ -0,0 +1,309 @@
+//! Deduplicated collections of venues
+//!
+//! The same venue is often returned by many overlapping queries, with
+//! different levels of detail. A `VenueSet` merges these into a single
+//! record per venue id.
+
+use std::collections::BTreeMap;
+use std::collections::btree_map::{IntoIter, Values};
+use std::iter::FromIterator;
+
+use super::{Location, Venue};
+
+/// overwrite optional fields with those of a newer record when present
+macro_rules! merge_options {
+    ($current:ident, $newer:ident, $($field:ident),*) => {
+        $(
+            if $newer.$field.is_some() {
+                $current.$field = $newer.$field;
+            }
+        )*
+    };
+}
+
+impl Location {
+    /// Merge a more recent record of this location into this one,
+    /// keeping existing optional fields the newer record lacks
+    pub fn merge(&mut self, newer: Location) {
+        merge_options!(
+            self,
+            newer,
+            address,
+            cross_street,
+            postal_code,
+            cc,
+            city,
+            state,
+            formatted_address
+        );
+        // distances are relative to the point of the search which returned
+        // each record, so an older distance is not kept
+        self.distance = newer.distance;
+        self.lat = newer.lat;
+        self.lng = newer.lng;
+        self.country = newer.country;
+    }
+}
+
+impl Venue {
+    /// Merge a more recent record of this venue into this one, keeping
+    /// existing optional fields the newer record lacks
+    pub fn merge(&mut self, newer: Venue) {
+        merge_options!(
+            self,
+            newer,
+            contact,
+            verified,
+            stats,
+            url,
+            hours,
+            has_menu,
+            menu,
+            price,
+            here_now,
+            created_at,
+            photos,
+            tips,
+            listed,
+            been_here,
+            specials,
+            likes,
+            dislike,
+            popular,
+            best_photo,
+            short_url,
+            store_id,
+            page,
+            reasons,
+            delivery,
+            referral_id,
+            has_perk,
+            rating,
+            rating_signals,
+            time_zone,
+            attributes,
+            canonical_url,
+            closed
+        );
+        self.name = newer.name;
+        if !newer.categories.is_empty() {
+            self.categories = newer.categories;
+        }
+        self.location.merge(newer.location);
+    }
+}
+
+/// A collection of venues keyed by venue id
+///
+/// Venues inserted with an id already in the set are merged into the
+/// existing record, so insert venues in the order they were fetched.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct VenueSet {
+    venues: BTreeMap<String, Venue>,
+}
+
+impl VenueSet {
+    /// Return a new empty set
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Insert a venue, merging it into any existing record with the same
+    /// id. Returns true if the venue was not already present
+    pub fn insert(&mut self, venue: Venue) -> bool {
+        if let Some(existing) = self.venues.get_mut(&venue.id) {
+            existing.merge(venue);
+            return false;
+        }
+        self.venues.insert(venue.id.clone(), venue);
+        true
+    }
+
+    /// Return the venue with the provided id
+    pub fn get(&self, id: &str) -> Option<&Venue> {
+        self.venues.get(id)
+    }
+
+    /// Return true if the set contains a venue with the provided id
+    pub fn contains(&self, id: &str) -> bool {
+        self.venues.contains_key(id)
+    }
+
+    /// Remove and return the venue with the provided id
+    pub fn remove(&mut self, id: &str) -> Option<Venue> {
+        self.venues.remove(id)
+    }
+
+    /// Return the number of venues in the set
+    pub fn len(&self) -> usize {
+        self.venues.len()
+    }
+
+    /// Return true if the set has no venues
+    pub fn is_empty(&self) -> bool {
+        self.venues.is_empty()
+    }
+
+    /// Return an iterator over venues, ordered by id
+    pub fn iter(&self) -> Values<String, Venue> {
+        self.venues.values()
+    }
+}
+
+impl Extend<Venue> for VenueSet {
+    fn extend<I>(&mut self, venues: I)
+    where
+        I: IntoIterator<Item = Venue>,
+    {
+        for venue in venues {
+            self.insert(venue);
+        }
+    }
+}
+
+impl FromIterator<Venue> for VenueSet {
+    fn from_iter<I>(venues: I) -> Self
+    where
+        I: IntoIterator<Item = Venue>,
+    {
+        let mut set = VenueSet::new();
+        set.extend(venues);
+        set
+    }
+}
+
+impl IntoIterator for VenueSet {
+    type Item = Venue;
+    type IntoIter = ::std::iter::Map<
+        IntoIter<String, Venue>,
+        fn((String, Venue)) -> Venue,
+    >;
+
+    fn into_iter(self) -> Self::IntoIter {
+        fn venue((_, venue): (String, Venue)) -> Venue {
+            venue
+        }
+        self.venues.into_iter().map(venue)
+    }
+}
+
+impl<'a> IntoIterator for &'a VenueSet {
+    type Item = &'a Venue;
+    type IntoIter = Values<'a, String, Venue>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json;
+
+    fn venue(json: &str) -> Venue {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn merges_duplicates() {
+        let mut set = VenueSet::new();
+        assert!(set.insert(venue(
+            r#"{"id": "1", "name": "Cafe", "categories": [],
+                "url": "http://cafe.com", "timeZone": "Asia/Seoul",
+                "location": {"lat": 37.5, "lng": 126.9, "country": "KR",
+                             "address": "1 Main St"}}"#,
+        )));
+        assert!(!set.insert(venue(
+            r#"{"id": "1", "name": "Cafe Renamed", "categories": [],
+                "rating": 9.1,
+                "location": {"lat": 37.5, "lng": 126.9, "country": "KR",
+                             "distance": 120}}"#,
+        )));
+        assert_eq!(set.len(), 1);
+        let merged = set.get("1").unwrap();
+        assert_eq!(merged.name, "Cafe Renamed");
+        assert_eq!(merged.url, Some("http://cafe.com".into()));
+        assert_eq!(merged.rating, Some(9.1));
+        assert_eq!(merged.location.address, Some("1 Main St".into()));
+        assert_eq!(merged.location.distance, Some(120));
+
+        let resumed = serde_json::from_str::<VenueSet>(
+            &serde_json::to_string(&set).unwrap(),
+        ).unwrap();
+        assert_eq!(
+            resumed.get("1").unwrap().time_zone,
+            Some("Asia/Seoul".into())
+        )
+    }
+
+    #[test]
+    fn merges_every_field() {
+        let full = venue(
+            r#"{"id": "1", "name": "Cafe",
+                "contact": {"phone": "1"},
+                "location": {"address": "1 Main St", "crossStreet": "2nd Ave",
+                             "lat": 37.5, "lng": 126.9, "distance": 10,
+                             "postalCode": "04524", "cc": "KR",
+                             "city": "Seoul", "state": "Seoul",
+                             "country": "KR",
+                             "formattedAddress": ["1 Main St"]},
+                "categories": [],
+                "verified": true,
+                "stats": {"checkinsCount": 1},
+                "url": "http://cafe.com",
+                "hours": {"isOpen": true, "isLocalHoliday": false},
+                "hasMenu": true,
+                "menu": {"label": "Menu", "url": "http://cafe.com/menu"},
+                "price": {"tier": 1, "message": "Cheap", "currency": "$"},
+                "hereNow": {"count": 0},
+                "createdAt": 1500000000,
+                "photos": {"count": 0, "groups": []},
+                "tips": {"count": 0},
+                "listed": {"count": 0},
+                "beenHere": {"count": 0},
+                "specials": {"count": 0},
+                "likes": {"count": 0},
+                "dislike": false,
+                "popular": {"isOpen": false, "isLocalHoliday": false},
+                "bestPhoto": {"id": "p", "prefix": "p", "suffix": ".jpg",
+                              "width": 1, "height": 1,
+                              "visibility": "public"},
+                "shortUrl": "http://4sq.com/1",
+                "storeId": "s",
+                "page": {},
+                "reasons": {"count": 0},
+                "delivery": {"id": "d", "url": "http://delivery.com",
+                             "provider": {"name": "delivery"}},
+                "referralId": "v-1",
+                "hasPerk": false,
+                "rating": 9.1,
+                "ratingSignals": 10,
+                "timeZone": "Asia/Seoul",
+                "attributes": {"groups": []},
+                "canonicalUrl": "https://foursquare.com/v/cafe/1",
+                "closed": false}"#,
+        );
+        let full = serde_json::to_value(&full).unwrap();
+        // when a field is added to Venue or Location, populate it above
+        for fields in &[&full, &full["location"]] {
+            for (name, value) in fields.as_object().unwrap() {
+                assert!(!value.is_null(), "{} is not populated", name)
+            }
+        }
+
+        let mut merged = venue(
+            r#"{"id": "1", "name": "Cafe", "categories": [],
+                "location": {"lat": 37.5, "lng": 126.9, "country": "KR"}}"#,
+        );
+        merged.merge(serde_json::from_value(full.clone()).unwrap());
+        assert_eq!(serde_json::to_value(&merged).unwrap(), full);
+
+        // distances from another search are not kept
+        merged.merge(venue(
+            r#"{"id": "1", "name": "Cafe", "categories": [],
+                "location": {"lat": 37.5, "lng": 126.9, "country": "KR"}}"#,
+        ));
+        assert_eq!(merged.location.distance, None)
+    }
+}